@@ -0,0 +1,192 @@
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use shrev::EventChannel;
+
+fn single_write(c: &mut Criterion) {
+    let mut group = c.benchmark_group("single_write");
+    group.throughput(Throughput::Elements(1_000));
+    group.bench_function("1000 events", |b| {
+        let mut channel = EventChannel::with_capacity(1_024);
+        let mut reader = channel.register_reader();
+
+        b.iter(|| {
+            for i in 0..1_000u32 {
+                channel.single_write(black_box(i));
+            }
+            black_box(channel.read(&mut reader).count());
+        });
+    });
+    group.finish();
+}
+
+fn single_write_readers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("single_write_readers");
+    for &readers in &[1usize, 64, 1_024] {
+        group.throughput(Throughput::Elements(1_000));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(readers),
+            &readers,
+            |b, &readers| {
+                let mut channel = EventChannel::with_capacity(1_024);
+                let mut lagging = channel.register_reader();
+                let mut readers = (1..readers)
+                    .map(|_| channel.register_reader())
+                    .collect::<Vec<_>>();
+
+                // Only measure the writes; the lagging reader keeps the buffer
+                // close to full, so writes regularly have to look for it
+                b.iter_custom(|iters| {
+                    let mut elapsed = Duration::ZERO;
+                    for _ in 0..iters {
+                        let start = Instant::now();
+                        for i in 0..1_000u32 {
+                            channel.single_write(black_box(i));
+                        }
+                        elapsed += start.elapsed();
+
+                        for reader in &mut readers {
+                            black_box(channel.read(reader).count());
+                        }
+                        black_box(channel.read_until(&mut lagging, |&e| e == 499).count());
+                    }
+
+                    elapsed
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+fn max_lag(c: &mut Criterion) {
+    c.bench_function("max_lag 1024 readers", |b| {
+        let mut channel = EventChannel::with_capacity(1_024);
+        let mut readers = (0..1_024)
+            .map(|_| channel.register_reader())
+            .collect::<Vec<_>>();
+
+        b.iter(|| {
+            // Polling doesn't need to look at every reader while the slowest
+            // one stays the same
+            for i in 0..100u32 {
+                channel.single_write(black_box(i));
+                black_box(channel.max_lag());
+            }
+            for reader in &mut readers {
+                black_box(channel.read(reader).count());
+            }
+        });
+    });
+}
+
+fn iter_write(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iter_write");
+    for &batch in &[1u32, 16, 256, 4_096] {
+        group.throughput(Throughput::Elements(batch as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(batch), &batch, |b, &batch| {
+            let mut channel = EventChannel::with_capacity(8_192);
+            let mut reader = channel.register_reader();
+
+            b.iter(|| {
+                channel.iter_write(black_box(0..batch));
+                black_box(channel.read(&mut reader).count());
+            });
+        });
+    }
+    group.finish();
+}
+
+fn read(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read");
+    for &readers in &[1usize, 4, 16] {
+        group.throughput(Throughput::Elements(1_000 * readers as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(readers),
+            &readers,
+            |b, &readers| {
+                let mut channel = EventChannel::with_capacity(1_024);
+                let mut readers = (0..readers)
+                    .map(|_| channel.register_reader())
+                    .collect::<Vec<_>>();
+
+                // Only measure the reads, not the write making the events available
+                b.iter_custom(|iters| {
+                    let mut elapsed = Duration::ZERO;
+                    for _ in 0..iters {
+                        channel.iter_write(0..1_000u32);
+
+                        let start = Instant::now();
+                        for reader in &mut readers {
+                            black_box(channel.read(reader).sum::<u32>());
+                        }
+                        elapsed += start.elapsed();
+                    }
+
+                    elapsed
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+fn power_of_two(c: &mut Criterion) {
+    let mut group = c.benchmark_group("power_of_two");
+    group.throughput(Throughput::Elements(1_000));
+    // Both channels have room for all events, only the index arithmetic differs
+    for &capacity in &[1_000usize, 1_024] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(capacity),
+            &capacity,
+            |b, &capacity| {
+                let mut channel = EventChannel::with_capacity(capacity);
+                let mut reader = channel.register_reader();
+
+                b.iter(|| {
+                    for i in 0..1_000u32 {
+                        channel.single_write(black_box(i));
+                    }
+                    black_box(channel.read(&mut reader).count());
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+fn grow(c: &mut Criterion) {
+    c.bench_function("grow", |b| {
+        b.iter_batched(
+            || {
+                let mut channel = EventChannel::with_capacity(1_024);
+                let reader = channel.register_reader();
+                channel.iter_write(0..1_024u32);
+
+                (channel, reader)
+            },
+            |(mut channel, reader)| {
+                // The reader hasn't read anything, so this has to grow the buffer
+                channel.single_write(black_box(1_024));
+
+                (channel, reader)
+            },
+            BatchSize::SmallInput,
+        );
+    });
+}
+
+criterion_group!(
+    benches,
+    single_write,
+    single_write_readers,
+    max_lag,
+    iter_write,
+    read,
+    power_of_two,
+    grow
+);
+criterion_main!(benches);
@@ -0,0 +1,94 @@
+//! Buffered writes to an `EventChannel`.
+
+use std::mem;
+
+use crate::{Event, EventChannel};
+
+/// Collects single events and writes them to the channel at once, see
+/// `EventChannel::batch_writer`.
+///
+/// The events only become visible to readers once the batch is flushed,
+/// either with `flush` or by dropping the `BatchWriter`.
+#[derive(Debug)]
+pub struct BatchWriter<'a, E: Event> {
+    channel: &'a mut EventChannel<E>,
+    events: Vec<E>,
+}
+
+impl<'a, E> BatchWriter<'a, E>
+where
+    E: Event,
+{
+    /// Add `event` to the batch.
+    ///
+    /// Readers won't observe it until the batch is flushed.
+    pub fn write(&mut self, event: E) {
+        self.events.push(event);
+    }
+
+    /// Returns the number of events waiting to be flushed.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns `true` if no events are waiting to be flushed.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Write all events of the batch to the channel, growing it at most once.
+    pub fn flush(&mut self) {
+        self.channel.drain_vec_write(&mut self.events);
+    }
+}
+
+impl<'a, E> Drop for BatchWriter<'a, E>
+where
+    E: Event,
+{
+    fn drop(&mut self) {
+        self.flush();
+        // Hand the allocation back for the next batch
+        self.channel.scratch = mem::take(&mut self.events);
+    }
+}
+
+impl<E> EventChannel<E>
+where
+    E: Event,
+{
+    /// Returns a `BatchWriter` for writing many single events, which defers
+    /// the capacity check and growth of the channel until the batch is
+    /// flushed.
+    ///
+    /// Events written to the `BatchWriter` are **not visible** to readers
+    /// before it is flushed with `BatchWriter::flush` or dropped. Since the
+    /// `BatchWriter` borrows the channel, no reads can happen in the meantime
+    /// anyway.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    ///
+    /// let mut batch = channel.batch_writer();
+    /// for i in 0..100 {
+    ///     batch.write(i);
+    /// }
+    /// drop(batch);
+    ///
+    /// assert_eq!(channel.read(&mut reader).len(), 100);
+    /// ```
+    pub fn batch_writer(&mut self) -> BatchWriter<'_, E> {
+        let mut events = mem::take(&mut self.scratch);
+        events.clear();
+
+        BatchWriter {
+            channel: self,
+            events,
+        }
+    }
+}
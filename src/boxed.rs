@@ -0,0 +1,82 @@
+//! An event channel storing its events on the heap.
+
+use crate::{DEFAULT_CAPACITY, Event, EventChannel, ReaderId};
+
+/// An `EventChannel` which boxes every event, so its ring buffer only holds
+/// pointers.
+///
+/// This is useful if some rare variants make the event type large: the
+/// memory of the buffer no longer scales with `size_of::<E>()`, at the cost
+/// of an allocation per event. Events are written and read as `E`; the
+/// boxing is transparent.
+///
+/// ## Examples
+///
+/// ```
+/// use shrev::BoxedEventChannel;
+///
+/// enum Event {
+///     Small(u8),
+///     Large([u8; 4096]),
+/// }
+///
+/// let mut channel = BoxedEventChannel::new();
+/// let mut reader = channel.register_reader();
+///
+/// channel.single_write(Event::Small(1));
+/// channel.single_write(Event::Large([0; 4096]));
+/// assert!(matches!(channel.read(&mut reader).next(), Some(Event::Small(1))));
+/// ```
+#[derive(Debug)]
+pub struct BoxedEventChannel<E> {
+    storage: EventChannel<Box<E>>,
+}
+
+impl<E> Default for BoxedEventChannel<E>
+where
+    E: Event,
+{
+    fn default() -> Self {
+        BoxedEventChannel::with_capacity(DEFAULT_CAPACITY)
+    }
+}
+
+impl<E> BoxedEventChannel<E>
+where
+    E: Event,
+{
+    channel_basics!("BoxedEventChannel", EventChannel::with_nonzero_capacity);
+
+    /// Register a new reader, see `EventChannel::register_reader`.
+    pub fn register_reader(&mut self) -> ReaderId<Box<E>> {
+        self.storage.register_reader()
+    }
+
+    /// Write an iterator of events into storage, boxing each of them.
+    pub fn iter_write<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = E>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.storage.iter_write(iter.into_iter().map(Box::new));
+    }
+
+    /// Write a single event into storage, boxing it.
+    pub fn single_write(&mut self, event: E) {
+        self.storage.single_write(Box::new(event));
+    }
+
+    /// Read any events that have been written to storage since the last read
+    /// with `reader_id`, see `EventChannel::read`.
+    pub fn read(
+        &self,
+        reader_id: &mut ReaderId<Box<E>>,
+    ) -> impl ExactSizeIterator<Item = &E> + DoubleEndedIterator {
+        self.storage.read(reader_id).map(|event| &**event)
+    }
+
+    /// Returns the underlying channel of boxed events.
+    pub fn as_channel(&mut self) -> &mut EventChannel<Box<E>> {
+        &mut self.storage
+    }
+}
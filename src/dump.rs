@@ -0,0 +1,78 @@
+//! Writing events to and reading them from byte streams with `serde`.
+
+use std::{
+    convert::TryFrom,
+    io::{self, BufRead, Read, Write},
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+/// The format `EventChannel::drain_to` writes events in, and
+/// `EventChannel::load_from` reads them in.
+///
+/// Both formats encode every event as JSON; they differ in how the events
+/// are separated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DumpFormat {
+    /// One event per line.
+    JsonLines,
+    /// Every event is preceded by its length in bytes, as a little endian
+    /// `u32`.
+    LengthPrefixed,
+}
+
+impl DumpFormat {
+    pub(crate) fn write<W, E>(self, w: &mut W, event: &E) -> io::Result<()>
+    where
+        W: Write,
+        E: Serialize,
+    {
+        let bytes = serde_json::to_vec(event)?;
+        match self {
+            DumpFormat::JsonLines => {
+                w.write_all(&bytes)?;
+                w.write_all(b"\n")
+            }
+            DumpFormat::LengthPrefixed => {
+                let len = u32::try_from(bytes.len())
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "event too large"))?;
+                w.write_all(&len.to_le_bytes())?;
+                w.write_all(&bytes)
+            }
+        }
+    }
+
+    /// Reads the next event, or returns `None` at the end of the stream or if
+    /// the next record is incomplete or corrupt.
+    pub(crate) fn read<R, E>(self, r: &mut R) -> io::Result<Option<E>>
+    where
+        R: BufRead,
+        E: DeserializeOwned,
+    {
+        let mut bytes = Vec::new();
+        match self {
+            DumpFormat::JsonLines => {
+                // A line without its newline may have been cut off
+                if r.read_until(b'\n', &mut bytes)? == 0 || bytes.pop() != Some(b'\n') {
+                    return Ok(None);
+                }
+            }
+            DumpFormat::LengthPrefixed => {
+                let mut len = [0; 4];
+                if r.take(4).read_to_end(&mut bytes)? < len.len() {
+                    return Ok(None);
+                }
+                len.copy_from_slice(&bytes);
+                let len = u32::from_le_bytes(len) as usize;
+
+                // Not allocated up front, in case the length is corrupt
+                bytes.clear();
+                if r.take(len as u64).read_to_end(&mut bytes)? < len {
+                    return Ok(None);
+                }
+            }
+        }
+
+        Ok(serde_json::from_slice(&bytes).ok())
+    }
+}
@@ -0,0 +1,79 @@
+//! Error types returned by `EventChannel` methods.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+/// Returned by `EventChannel::read_checked` if the first pending event
+/// doesn't carry the sequence number following the last event the reader
+/// observed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GapError {
+    /// The sequence number the reader expected next.
+    pub expected: u64,
+    /// The sequence number of the first pending event.
+    pub got: u64,
+}
+
+impl Display for GapError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "reader expected event #{}, but the next pending event is #{}",
+            self.expected, self.got
+        )
+    }
+}
+
+impl Error for GapError {}
+
+/// Returned by `EventChannel::commit_read` if the reader was advanced after
+/// the read began.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StaleReadError;
+
+impl Display for StaleReadError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("the reader was advanced after the read began")
+    }
+}
+
+impl Error for StaleReadError {}
+
+/// Returned by `EventChannel::read_strict` if the stored position of a reader
+/// doesn't fit the state of the buffer.
+///
+/// This indicates a bug in shrev.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConsistencyError {
+    pub(crate) violated: &'static str,
+}
+
+impl Display for ConsistencyError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "inconsistent reader state: {}", self.violated)
+    }
+}
+
+impl Error for ConsistencyError {}
+
+/// Returned by `EventChannel::try_register_reader` if the channel already has
+/// the maximum number of readers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TooManyReaders {
+    /// The limit set with `EventChannel::set_max_readers`.
+    pub max: usize,
+}
+
+impl Display for TooManyReaders {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "the channel already has the maximum of {} readers",
+            self.max
+        )
+    }
+}
+
+impl Error for TooManyReaders {}
@@ -0,0 +1,98 @@
+//! An event channel with a fixed maximum number of readers.
+
+use crate::{
+    DEFAULT_CAPACITY, Event, EventIterator, ReaderId,
+    storage::{FixedSlots, RingBuffer},
+};
+
+/// An `EventChannel` which supports at most `N` readers at a time.
+///
+/// The reader metadata is stored inline, so registering and dropping readers
+/// never allocates. Events are stored in the same growable ring buffer as in
+/// `EventChannel`. Note that dropping a `ReaderId` still notifies the channel
+/// through a queue, which may allocate.
+///
+/// ## Examples
+///
+/// ```
+/// use shrev::FixedEventChannel;
+///
+/// let mut channel = FixedEventChannel::<u32, 2>::new();
+/// let mut reader = channel.register_reader();
+/// let _second = channel.register_reader();
+/// assert!(channel.try_register_reader().is_none());
+///
+/// channel.single_write(1);
+/// assert_eq!(channel.read(&mut reader).next(), Some(&1));
+/// ```
+#[derive(Debug)]
+pub struct FixedEventChannel<E, const N: usize> {
+    storage: RingBuffer<E, FixedSlots<N>>,
+}
+
+impl<E, const N: usize> Default for FixedEventChannel<E, N>
+where
+    E: Event,
+{
+    fn default() -> Self {
+        FixedEventChannel::with_capacity(DEFAULT_CAPACITY)
+    }
+}
+
+impl<E, const N: usize> FixedEventChannel<E, N>
+where
+    E: Event,
+{
+    channel_basics!("FixedEventChannel", RingBuffer::with_nonzero);
+
+    /// Register a new reader.
+    ///
+    /// Panics if `N` readers are registered already.
+    pub fn register_reader(&mut self) -> ReaderId<E> {
+        self.try_register_reader()
+            .unwrap_or_else(|| panic!("`FixedEventChannel` supports at most {} readers", N))
+    }
+
+    /// Register a new reader, returns `None` if `N` readers are registered
+    /// already.
+    pub fn try_register_reader(&mut self) -> Option<ReaderId<E>> {
+        self.storage.try_new_reader_id_back(0)
+    }
+
+    storage_write_read!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reader_limit() {
+        let mut channel = FixedEventChannel::<u32, 2>::with_capacity(2);
+        let mut first = channel.register_reader();
+        let second = channel.register_reader();
+        assert!(channel.try_register_reader().is_none());
+
+        drop(second);
+        let mut second = channel.register_reader();
+        assert!(channel.try_register_reader().is_none());
+
+        channel.iter_write(0..5);
+        assert_eq!(
+            channel.read(&mut first).cloned().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+        assert_eq!(
+            channel.read(&mut second).cloned().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "at most 1 readers")]
+    fn test_too_many_readers() {
+        let mut channel = FixedEventChannel::<u32, 1>::new();
+        let _first = channel.register_reader();
+        let _second = channel.register_reader();
+    }
+}
@@ -5,11 +5,52 @@
 
 #![warn(missing_docs)]
 
-pub use crate::storage::{ReaderId, StorageIterator as EventIterator};
+#[cfg(feature = "serde")]
+pub use crate::dump::DumpFormat;
+#[cfg(feature = "single-thread")]
+pub use crate::local::LocalEventChannel;
+pub use crate::{
+    batch::BatchWriter,
+    boxed::BoxedEventChannel,
+    error::{ConsistencyError, GapError, StaleReadError, TooManyReaders},
+    fixed::FixedEventChannel,
+    opts::{GrowthPolicy, RegisterOpts, StartPoint},
+    snapshot::EventSnapshot,
+    split::{Consumer, Producer},
+    storage::{
+        LimitedRead, PendingRead, Poll, Position, ReaderId, ReaderInfo, ReaderToken,
+        StorageChunks as EventChunks, StorageIterator as EventIterator,
+    },
+    subscription::Subscription,
+};
 
-use crate::storage::RingBuffer;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    num::NonZeroUsize,
+    ops::{AddAssign, Range},
+    time::Duration,
+};
 
+use crate::storage::{RingBuffer, StorageChunks};
+
+#[macro_use]
+mod macros;
+
+mod batch;
+mod boxed;
+#[cfg(feature = "serde")]
+mod dump;
+mod error;
+mod fixed;
+#[cfg(feature = "single-thread")]
+mod local;
+mod opts;
+mod snapshot;
+mod split;
 mod storage;
+mod subscription;
 mod util;
 
 /// Marker trait for data to use with the EventChannel.
@@ -19,7 +60,19 @@ pub trait Event: Send + Sync + 'static {}
 
 impl<T> Event for T where T: Send + Sync + 'static {}
 
-const DEFAULT_CAPACITY: usize = 64;
+/// The capacity of channels created with `new` or `default`.
+///
+/// ```
+/// use shrev::{DEFAULT_CAPACITY, EventChannel};
+///
+/// let channel = EventChannel::<u32>::new();
+/// assert_eq!(channel.capacity(), DEFAULT_CAPACITY);
+/// ```
+pub const DEFAULT_CAPACITY: usize = 64;
+
+/// The number of events the priority lane holds, see
+/// `EventChannel::single_write_priority`.
+pub const PRIORITY_CAPACITY: usize = 16;
 
 /// The `EventChannel`, which is the central component of `shrev`.
 ///
@@ -90,8 +143,14 @@ const DEFAULT_CAPACITY: usize = 64;
 #[derive(Debug)]
 pub struct EventChannel<E> {
     storage: RingBuffer<E>,
+    /// Buffers the events of `iter_write_from_fn`, `batch_writer` and
+    /// `par_extend`, kept to reuse the allocation
+    scratch: Vec<E>,
+    /// See `set_max_readers`
+    max_readers: Option<usize>,
 }
 
+/// Creates a channel with a capacity of `DEFAULT_CAPACITY`.
 impl<E> Default for EventChannel<E>
 where
     E: Event,
@@ -101,22 +160,294 @@ where
     }
 }
 
+/// Drains the vector into the channel, see `EventChannel::drain_vec_write`.
+///
+/// ```
+/// use shrev::EventChannel;
+///
+/// let mut channel = EventChannel::new();
+/// let mut events = vec![1, 2, 3];
+///
+/// channel += &mut events;
+/// assert!(events.is_empty());
+/// ```
+impl<E> AddAssign<&mut Vec<E>> for EventChannel<E>
+where
+    E: Event,
+{
+    fn add_assign(&mut self, events: &mut Vec<E>) {
+        self.drain_vec_write(events);
+    }
+}
+
+/// Collects the events of a parallel iterator in parallel, then writes them
+/// into the channel at once.
+///
+/// Requires the `rayon` feature.
+///
+/// ```
+/// use rayon::prelude::*;
+/// use shrev::EventChannel;
+///
+/// let mut channel = EventChannel::new();
+/// let mut reader = channel.register_reader();
+///
+/// channel.par_extend((0..1000u32).into_par_iter().map(|i| i * 2));
+/// assert!(channel.read(&mut reader).cloned().eq((0..1000).map(|i| i * 2)));
+/// ```
+#[cfg(feature = "rayon")]
+impl<E> rayon::iter::ParallelExtend<E> for EventChannel<E>
+where
+    E: Event,
+{
+    fn par_extend<I>(&mut self, par_iter: I)
+    where
+        I: rayon::iter::IntoParallelIterator<Item = E>,
+    {
+        self.scratch.clear();
+        self.scratch.par_extend(par_iter);
+        self.storage.drain_vec_write(&mut self.scratch);
+    }
+}
+
 impl<E> EventChannel<E>
 where
     E: Event,
 {
-    /// Create a new `EventChannel` with a default size of 64.
+    /// Create a new `EventChannel` with a default size of 64, see
+    /// `DEFAULT_CAPACITY`.
     pub fn new() -> Self {
         Default::default()
     }
 
     /// Create a new `EventChannel` with the given starting capacity.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `size` is zero; see `with_nonzero_capacity` for a constructor
+    /// that can't fail.
     pub fn with_capacity(size: usize) -> Self {
         Self {
             storage: RingBuffer::new(size),
+            scratch: Vec::new(),
+            max_readers: None,
+        }
+    }
+
+    /// Create a new `EventChannel` with the given starting capacity, and room
+    /// for `readers` readers.
+    ///
+    /// The first `readers` registrations don't need to reallocate the
+    /// bookkeeping of the readers. Nothing is registered yet, though.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn with_capacity_and_readers(size: usize, readers: usize) -> Self {
+        let mut channel = Self::with_capacity(size);
+        channel.storage.reserve_readers(readers);
+
+        channel
+    }
+
+    /// Create a new `EventChannel` with a starting capacity of `size`,
+    /// rounded up to the next power of two.
+    ///
+    /// The buffer doubles its capacity whenever it grows, so this way the
+    /// capacity is always a power of two, which allows for cheaper index
+    /// arithmetic. The price is up to twice the memory of `with_capacity`.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `size` is zero, or if the rounded capacity overflows
+    /// `usize`.
+    pub fn with_capacity_pow2(size: usize) -> Self {
+        let size = NonZeroUsize::new(size)
+            .expect("`EventChannel` capacity must be non-zero")
+            .get()
+            .checked_next_power_of_two()
+            .expect("`EventChannel` capacity overflows `usize`");
+
+        Self::with_capacity(size)
+    }
+
+    /// Create a new `EventChannel` with the given starting capacity.
+    pub fn with_nonzero_capacity(size: NonZeroUsize) -> Self {
+        Self {
+            storage: RingBuffer::with_nonzero(size),
+            scratch: Vec::new(),
+            max_readers: None,
+        }
+    }
+
+    /// Create a new `EventChannel` with the given starting capacity, whose
+    /// ring buffer is allocated with `alloc`, also when it grows.
+    ///
+    /// Requires the `allocator-api2` feature.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `size` is zero.
+    #[cfg(feature = "allocator-api2")]
+    pub fn with_capacity_in<A>(size: usize, alloc: A) -> Self
+    where
+        A: allocator_api2::alloc::Allocator + Send + Sync + 'static,
+    {
+        let size = NonZeroUsize::new(size).expect("`EventChannel` capacity must be non-zero");
+
+        Self {
+            storage: RingBuffer::with_nonzero_in(size, alloc),
+            scratch: Vec::new(),
+            max_readers: None,
         }
     }
 
+    /// Create a new `EventChannel` whose initial buffer takes up roughly
+    /// `bytes` bytes.
+    ///
+    /// The capacity is `bytes / size_of::<E>()`, but at least 2. Zero-sized
+    /// events don't take up any memory, so for them `DEFAULT_CAPACITY` is
+    /// used.
+    pub fn with_byte_budget(bytes: usize) -> Self {
+        let size = match std::mem::size_of::<E>() {
+            0 => DEFAULT_CAPACITY,
+            event_size => (bytes / event_size).max(2),
+        };
+
+        Self::with_capacity(size)
+    }
+
+    /// Sets whether the channel zeroes the memory of events it no longer
+    /// holds.
+    ///
+    /// With scrubbing enabled, a slot is cleared when its event is overwritten,
+    /// moved out by `merge_from_drain` or dropped with the channel, and the old
+    /// allocation is cleared when the buffer grows. This only covers the
+    /// channel's own buffer: heap memory owned by an event (like the contents
+    /// of a `String`) is released by its destructor, and copies made by
+    /// readers or moved out of the channel are up to the caller.
+    pub fn with_scrub(mut self, scrub: bool) -> Self {
+        self.storage.set_scrub(scrub);
+        self
+    }
+
+    /// Returns the number of events the buffer can currently hold.
+    ///
+    /// The buffer grows automatically, so this is not an upper bound on the
+    /// number of events.
+    pub fn capacity(&self) -> usize {
+        self.storage.capacity()
+    }
+
+    /// Grow the buffer so it holds at least `capacity` events, in a single
+    /// reallocation.
+    ///
+    /// Like automatic growth, this keeps doubling the capacity, so it might
+    /// end up larger than `capacity`. Does nothing if the capacity is already
+    /// large enough.
+    pub fn grow_to(&mut self, capacity: usize) {
+        self.storage.grow_to(capacity);
+    }
+
+    /// Move the stored events to the start of the buffer, if all readers are
+    /// caught up.
+    ///
+    /// After the buffer grew a few times, the stored events may be scattered
+    /// around the point where it wraps; compacting makes them contiguous
+    /// again, which helps with cache locality and makes the layout
+    /// predictable. The capacity stays the same. Does nothing if any reader
+    /// has unread events.
+    pub fn compact(&mut self) {
+        self.storage.compact();
+    }
+
+    /// Sets how the channel grows when a reader would miss events otherwise.
+    ///
+    /// The default is `GrowthPolicy::Double`. With `GrowthPolicy::Bounded`,
+    /// the channel stops growing at the given capacity and overwrites the
+    /// oldest events instead, even if readers haven't read them yet. Use
+    /// `poll` or `read_checked` to find out if a reader missed events.
+    ///
+    /// ```
+    /// use shrev::{EventChannel, GrowthPolicy, Poll};
+    ///
+    /// let mut channel = EventChannel::with_capacity(2);
+    /// channel.set_growth_policy(GrowthPolicy::Bounded { max: 4 });
+    /// let mut reader = channel.register_reader();
+    ///
+    /// channel.iter_write(0..6);
+    /// assert_eq!(channel.capacity(), 4);
+    /// match channel.poll(&mut reader) {
+    ///     Poll::Lagged { missed, iter } => {
+    ///         assert_eq!(missed, 2);
+    ///         assert_eq!(iter.cloned().collect::<Vec<_>>(), vec![2, 3, 4, 5]);
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the factor of `GrowthPolicy::Factor` isn't a finite number
+    /// greater than 1, or if the increment of `GrowthPolicy::FixedIncrement`
+    /// is zero.
+    pub fn set_growth_policy(&mut self, policy: GrowthPolicy) {
+        self.storage.set_growth_policy(policy);
+    }
+
+    /// Lets the channel shrink on its own after bursts, or turns that off
+    /// again with `None`, which is the default.
+    ///
+    /// Whenever all readers are caught up, the channel looks at how many
+    /// events were written since they were last caught up; that's the most
+    /// the readers ever lagged behind in the meantime. If it is less than
+    /// `ratio` times the capacity, the buffer is reallocated to the next
+    /// power of two that fits it, but never below the capacity the channel
+    /// was created with or the one set with `set_retention`. The newest
+    /// events which were read already are kept as far as they fit. The
+    /// channel never shrinks while a reader has unread events.
+    ///
+    /// Since reads only borrow the channel immutably, the check happens at
+    /// the start of the next write, before its events are stored.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::with_capacity(4);
+    /// channel.set_auto_shrink_ratio(Some(0.25));
+    /// let mut reader = channel.register_reader();
+    ///
+    /// channel.iter_write(0..100);
+    /// channel.read(&mut reader);
+    /// assert_eq!(channel.capacity(), 128);
+    ///
+    /// // Back to a steady trickle of events
+    /// for i in 0..2 {
+    ///     channel.single_write(i);
+    ///     channel.read(&mut reader);
+    /// }
+    /// assert_eq!(channel.capacity(), 4);
+    /// ```
+    pub fn set_auto_shrink_ratio(&mut self, ratio: Option<f32>) {
+        self.storage.set_auto_shrink(ratio);
+    }
+
+    /// Returns the number of events ever written to this channel.
+    ///
+    /// Every event gets a sequence number, counting up from 0 in the order
+    /// the events were written. Thus, this is also the sequence number the
+    /// next written event will get.
+    ///
+    /// Sequence numbers are `u64` on every target, so unlike a `usize`
+    /// counter on 32-bit targets, they don't wrap after about 4 billion
+    /// events. Wrapping a `u64` would take more than 500 years at a billion
+    /// events per second; the counter isn't expected to ever reach that
+    /// point, and sequence numbers like those of `events_between` would
+    /// be meaningless past it.
+    pub fn total_written(&self) -> u64 {
+        self.storage.total_written()
+    }
+
     /// Returns `true` if any reader would observe an additional event.
     ///
     /// This can be used to skip calls to `iter_write` in case the event
@@ -125,6 +456,68 @@ where
         self.storage.would_write()
     }
 
+    /// Returns how many events the slowest reader is behind.
+    ///
+    /// The buffer has to grow once this reaches the capacity, so this is a
+    /// good metric to monitor. Returns 0 if there are no readers or all of
+    /// them are caught up.
+    pub fn max_lag(&mut self) -> usize {
+        self.storage.max_lag()
+    }
+
+    /// Returns `max_lag` relative to the capacity, from `0.0` to `1.0`.
+    ///
+    /// This approaches `1.0` as the slowest reader falls a full lap behind,
+    /// at which point the next write grows the buffer. Unlike `max_lag`, it
+    /// can be compared across channels of different capacities.
+    pub fn utilization(&mut self) -> f32 {
+        self.max_lag() as f32 / self.capacity() as f32
+    }
+
+    /// Returns how many events can be written before the buffer has to grow,
+    /// given the current positions of the readers.
+    ///
+    /// This is the capacity minus `max_lag`. Without readers, the buffer
+    /// never grows, so even more events can be written.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::with_capacity(8);
+    /// let mut reader = channel.register_reader();
+    /// channel.iter_write(0..6);
+    /// assert_eq!(channel.available(), 2);
+    ///
+    /// channel.read(&mut reader);
+    /// assert_eq!(channel.available(), 8);
+    /// ```
+    pub fn available(&mut self) -> usize {
+        self.storage.available()
+    }
+
+    /// Returns `true` if the slowest reader is a full lap behind and the
+    /// channel can't grow, so the next write overwrites unread events.
+    ///
+    /// This only happens once a `GrowthPolicy::Bounded` channel reached its
+    /// maximum capacity; otherwise the channel grows instead, and this is
+    /// always `false`. To find out whether a write would have to grow the
+    /// channel, compare `max_lag` with the capacity.
+    ///
+    /// ```
+    /// use shrev::{EventChannel, GrowthPolicy};
+    ///
+    /// let mut channel = EventChannel::with_capacity(2);
+    /// let _reader = channel.register_reader();
+    /// channel.iter_write(0..2);
+    /// assert!(!channel.is_full());
+    ///
+    /// channel.set_growth_policy(GrowthPolicy::Bounded { max: 2 });
+    /// assert!(channel.is_full());
+    /// ```
+    pub fn is_full(&mut self) -> bool {
+        self.storage.is_full()
+    }
+
     /// Register a new reader.
     ///
     /// To be able to read events, a reader id is required. This is because
@@ -141,13 +534,195 @@ where
         self.storage.new_reader_id()
     }
 
+    /// Register a new reader like `register_reader`, unless the channel
+    /// already has as many readers as set with `set_max_readers`.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::<u32>::new();
+    /// channel.set_max_readers(Some(1));
+    ///
+    /// let reader = channel.try_register_reader().unwrap();
+    /// assert!(channel.try_register_reader().is_err());
+    ///
+    /// drop(reader);
+    /// assert!(channel.try_register_reader().is_ok());
+    /// ```
+    pub fn try_register_reader(&mut self) -> Result<ReaderId<E>, TooManyReaders> {
+        if let Some(max) = self.max_readers {
+            if self.reader_count() >= max {
+                return Err(TooManyReaders { max });
+            }
+        }
+
+        Ok(self.storage.new_reader_id())
+    }
+
+    /// Sets the maximum number of readers `try_register_reader` allows, or
+    /// removes the limit with `None`.
+    ///
+    /// This doesn't affect readers which are already registered, nor the other
+    /// `register_*` methods.
+    pub fn set_max_readers(&mut self, max: Option<usize>) {
+        self.max_readers = max;
+    }
+
+    /// Returns the number of registered readers, i.e. those whose `ReaderId`
+    /// has not been dropped.
+    pub fn reader_count(&mut self) -> usize {
+        self.storage.reader_count()
+    }
+
+    /// Register a new reader like `register_reader`, labeled with `name`.
+    ///
+    /// The name shows up in the `Debug` output of the `ReaderId` and in
+    /// diagnostics about the reader, like the message of
+    /// `assert_reader_drained` or the events of the `tracing` feature.
+    pub fn register_reader_named(&mut self, name: &'static str) -> ReaderId<E> {
+        self.storage.new_reader_id_named(name)
+    }
+
+    /// Register a new reader, which will observe the last `n` events still
+    /// stored in the channel before any new ones.
+    ///
+    /// If fewer than `n` events are stored, the reader observes all of them.
+    /// `register_reader_back(0)` is the same as `register_reader()`.
+    pub fn register_reader_back(&mut self, n: usize) -> ReaderId<E> {
+        self.storage.new_reader_id_back(n)
+    }
+
+    /// Register a new reader and return clones of all events currently
+    /// stored in the channel along with it, oldest first.
+    ///
+    /// The reader only observes the events written from now on, so the
+    /// returned events and the reader's reads together cover every event
+    /// exactly once. Since the channel is borrowed mutably, no write can
+    /// happen in between. This is the "initial state, then updates" pattern
+    /// of a subscription.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// channel.iter_write(0..3);
+    ///
+    /// let (snapshot, mut reader) = channel.register_reader_with_snapshot();
+    /// channel.single_write(3);
+    ///
+    /// assert_eq!(snapshot, vec![0, 1, 2]);
+    /// assert_eq!(channel.read(&mut reader).cloned().collect::<Vec<_>>(), vec![3]);
+    /// ```
+    pub fn register_reader_with_snapshot(&mut self) -> (Vec<E>, ReaderId<E>)
+    where
+        E: Clone,
+    {
+        let reader_id = self.storage.new_reader_id();
+
+        (self.storage.iter_all().cloned().collect(), reader_id)
+    }
+
+    /// Register a new reader, positioned as specified by `opts`.
+    ///
+    /// ```
+    /// use shrev::{EventChannel, RegisterOpts, StartPoint};
+    ///
+    /// let mut channel = EventChannel::new();
+    /// channel.iter_write(0..4);
+    ///
+    /// let mut reader = channel.register_reader_opts(RegisterOpts {
+    ///     from: StartPoint::Back(2),
+    /// });
+    /// assert_eq!(channel.read(&mut reader).cloned().collect::<Vec<_>>(), vec![2, 3]);
+    /// ```
+    pub fn register_reader_opts(&mut self, opts: RegisterOpts) -> ReaderId<E> {
+        let n = match opts.from {
+            StartPoint::Head => 0,
+            // Clamped to the number of stored events
+            StartPoint::Oldest => usize::MAX,
+            StartPoint::Back(n) => n,
+        };
+
+        self.storage.new_reader_id_back(n)
+    }
+
+    /// Register a new reader at the current position of `reader_id`, so it
+    /// observes the same events with its next read.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `reader_id` doesn't belong to this channel.
+    pub fn clone_reader(&mut self, reader_id: &ReaderId<E>) -> ReaderId<E> {
+        self.storage.clone_reader_id(reader_id)
+    }
+
+    /// Returns the token of the reader furthest behind, i.e. the one which
+    /// forces the channel to grow when it is full, or `None` if there are no
+    /// readers.
+    ///
+    /// Events of the priority lane are not taken into account.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut fast = channel.register_reader_named("fast");
+    /// let slow = channel.register_reader_named("slow");
+    /// channel.iter_write(0..3);
+    /// channel.read(&mut fast);
+    ///
+    /// assert_eq!(channel.slowest_reader(), Some(slow.token()));
+    /// ```
+    pub fn slowest_reader(&mut self) -> Option<ReaderToken> {
+        self.storage.slowest_reader()
+    }
+
+    /// Returns the state of every registered reader, without needing their
+    /// `ReaderId`s.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut fast = channel.register_reader_named("fast");
+    /// let _slow = channel.register_reader_named("slow");
+    /// channel.iter_write(0..3);
+    /// channel.read(&mut fast);
+    ///
+    /// let lagging = channel
+    ///     .readers_iter()
+    ///     .filter(|info| info.unread > 0)
+    ///     .filter_map(|info| info.name)
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(lagging, vec!["slow"]);
+    /// ```
+    pub fn readers_iter(&mut self) -> impl Iterator<Item = ReaderInfo> + '_ {
+        self.storage.readers_info()
+    }
+
+    /// Returns `true` if the reader `token` was created from is still
+    /// registered, i.e. its `ReaderId` has not been dropped.
+    ///
+    /// Tokens of other channels are never active.
+    pub fn is_active(&mut self, token: ReaderToken) -> bool {
+        self.storage.is_active(token)
+    }
+
     /// Write a slice of events into storage
-    #[deprecated(note = "please use `iter_write` instead")]
+    #[deprecated(note = "please use `clone_write` instead")]
     pub fn slice_write(&mut self, events: &[E])
     where
         E: Clone,
     {
-        self.storage.iter_write(events.into_iter().cloned());
+        self.clone_write(events);
+    }
+
+    /// Write clones of a slice of events into storage.
+    pub fn clone_write(&mut self, events: &[E])
+    where
+        E: Clone,
+    {
+        self.storage.iter_write(events.iter().cloned());
     }
 
     /// Write an iterator of events into storage
@@ -159,141 +734,2246 @@ where
         self.storage.iter_write(iter);
     }
 
+    /// Write an iterator of events into storage like `iter_write`, and
+    /// return the range of sequence numbers they were assigned.
+    ///
+    /// The range can be passed to `events_between` later on, as long as the
+    /// events are still stored. It is empty if `iter` is.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// channel.iter_write(0..10);
+    ///
+    /// let seq = channel.iter_write_seq(vec![20, 21]);
+    /// assert_eq!(seq, 10..12);
+    /// let events = channel.events_between(seq.start, seq.end).unwrap();
+    /// assert_eq!(events.cloned().collect::<Vec<_>>(), vec![20, 21]);
+    /// ```
+    pub fn iter_write_seq<I>(&mut self, iter: I) -> Range<u64>
+    where
+        I: IntoIterator<Item = E>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let start = self.storage.total_written();
+        self.storage.iter_write(iter);
+
+        start..self.storage.total_written()
+    }
+
     /// Drain a vector of events into storage.
     pub fn drain_vec_write(&mut self, events: &mut Vec<E>) {
         self.storage.drain_vec_write(events);
     }
 
-    /// Write a single event into storage.
-    pub fn single_write(&mut self, event: E) {
-        self.storage.single_write(event);
-    }
-
-    /// Read any events that have been written to storage since the last read
-    /// with `reader_id` (or the creation of the `ReaderId`, if it hasn't read
-    /// yet).
+    /// Drain the first `n` events of a vector into storage, leaving the rest
+    /// in the vector. If it holds fewer than `n` events, all are written.
     ///
-    /// Note that this will advance the position of the reader regardless of
-    /// what you do with the iterator. In other words, calling `read`
-    /// without iterating the result won't preserve the events returned. You
-    /// need to iterate all the events as soon as you got them from this
-    /// method. This behavior is equivalent to e.g. `Vec::drain`.
-    pub fn read(&self, reader_id: &mut ReaderId<E>) -> EventIterator<E> {
-        self.storage.read(reader_id)
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    /// let mut events = vec![1, 2, 3];
+    ///
+    /// channel.drain_vec_write_n(&mut events, 2);
+    /// assert_eq!(events, vec![3]);
+    /// assert_eq!(channel.read(&mut reader).cloned().collect::<Vec<_>>(), vec![1, 2]);
+    /// ```
+    pub fn drain_vec_write_n(&mut self, events: &mut Vec<E>, n: usize) {
+        let n = n.min(events.len());
+        self.storage.iter_write(events.drain(..n));
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
-    #[derive(Debug, Clone, PartialEq)]
-    struct Test {
-        pub id: u32,
+    /// Drain a `VecDeque` of events into storage, front to back.
+    pub fn drain_deque_write(&mut self, events: &mut VecDeque<E>) {
+        self.storage.iter_write(events.drain(..));
     }
 
-    #[test]
-    fn test_grow() {
-        let mut channel = EventChannel::with_capacity(10);
-
-        let mut reader0 = channel.register_reader();
-        let mut reader1 = channel.register_reader();
-
-        channel.iter_write(vec![1, 2, 3, 4, 5, 6, 7, 8]);
-
-        let data = channel.read(&mut reader0).cloned().collect::<Vec<_>>();
-        assert_eq!(data, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    /// Write the events `f` returns until it returns `None`, and return how
+    /// many events were written.
+    ///
+    /// This is like `iter_write(std::iter::from_fn(f))`, which isn't possible
+    /// because `iter_write` needs to know the number of events up front. The
+    /// events are collected into a buffer first, which is kept by the channel
+    /// to be reused by the next call.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    ///
+    /// let mut n = 0;
+    /// let written = channel.iter_write_from_fn(|| {
+    ///     n += 1;
+    ///     Some(n).filter(|&n| n <= 3)
+    /// });
+    /// assert_eq!(written, 3);
+    /// assert_eq!(channel.read(&mut reader).cloned().collect::<Vec<_>>(), vec![1, 2, 3]);
+    /// ```
+    pub fn iter_write_from_fn<F>(&mut self, f: F) -> usize
+    where
+        F: FnMut() -> Option<E>,
+    {
+        // Might hold events from a call where `f` panicked
+        self.scratch.clear();
+        self.scratch.extend(std::iter::from_fn(f));
+        let written = self.scratch.len();
+        self.storage.drain_vec_write(&mut self.scratch);
 
-        channel.iter_write(vec![9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22]);
+        written
+    }
 
-        let data = channel.read(&mut reader0).cloned().collect::<Vec<_>>();
-        assert_eq!(
-            data,
-            vec![9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22]
-        );
+    /// Write the events of `iter`, keeping only the last event of the batch
+    /// for every key returned by `key`.
+    ///
+    /// This collapses redundant events, like several updates of the same
+    /// entity, into the latest one. The events that are kept are written in
+    /// the order they appear in `iter`, so an event is placed where the last
+    /// event with its key was, not the first one. Events written before are
+    /// not taken into account. Like `iter_write_from_fn`, this collects the
+    /// events into a buffer kept by the channel first.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    ///
+    /// let updates = vec![(1, "a"), (2, "b"), (1, "c"), (3, "d")];
+    /// channel.iter_write_dedup_by_key(updates, |&(entity, _)| entity);
+    /// assert_eq!(
+    ///     channel.read(&mut reader).cloned().collect::<Vec<_>>(),
+    ///     vec![(2, "b"), (1, "c"), (3, "d")]
+    /// );
+    /// ```
+    pub fn iter_write_dedup_by_key<I, K, F>(&mut self, iter: I, mut key: F)
+    where
+        I: IntoIterator<Item = E>,
+        K: Eq + Hash,
+        F: FnMut(&E) -> K,
+    {
+        self.scratch.clear();
+        self.scratch.extend(iter);
 
-        for i in 23..10_000 {
-            channel.single_write(i);
+        let mut last = HashMap::with_capacity(self.scratch.len());
+        for (i, event) in self.scratch.iter().enumerate() {
+            last.insert(key(event), i);
+        }
+        let mut keep = vec![false; self.scratch.len()];
+        for i in last.into_values() {
+            keep[i] = true;
         }
+        let mut keep = keep.into_iter();
+        self.scratch.retain(|_| keep.next() == Some(true));
 
-        let data = channel.read(&mut reader1).cloned().collect::<Vec<_>>();
-        assert_eq!(data, (1..10_000).collect::<Vec<_>>());
+        self.storage.drain_vec_write(&mut self.scratch);
     }
 
-    #[test]
-    fn test_read_write() {
-        let mut channel = EventChannel::with_capacity(14);
+    /// Sets a closure which is called with every event written to the
+    /// channel from now on, replacing the previous one.
+    ///
+    /// The observer sees every event as it is written, without registering
+    /// a reader, so it never keeps the buffer from overwriting events. That
+    /// makes it a cheap way to count or log events. It runs synchronously on
+    /// the writing thread, right before each event is stored, so it should
+    /// return quickly and must not block.
+    ///
+    /// ```
+    /// use std::sync::{
+    ///     Arc,
+    ///     atomic::{AtomicUsize, Ordering},
+    /// };
+    ///
+    /// use shrev::EventChannel;
+    ///
+    /// let written = Arc::new(AtomicUsize::new(0));
+    /// let mut channel = EventChannel::new();
+    /// let counter = written.clone();
+    /// channel.set_write_observer(Box::new(move |_: &u32| {
+    ///     counter.fetch_add(1, Ordering::Relaxed);
+    /// }));
+    ///
+    /// channel.iter_write(0..3);
+    /// channel.single_write(3);
+    /// assert_eq!(written.load(Ordering::Relaxed), 4);
+    /// ```
+    pub fn set_write_observer(&mut self, f: Box<dyn FnMut(&E) + Send>) {
+        self.storage.set_observer(f);
+    }
 
-        let mut reader_id = channel.register_reader();
+    /// Write a single event into storage.
+    pub fn single_write(&mut self, event: E) {
+        self.storage.single_write(event);
+    }
+
+    /// Write a single event into storage, returning the number of readers
+    /// which will observe it.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let _first = channel.register_reader();
+    /// let second = channel.register_reader();
+    /// drop(second);
+    ///
+    /// assert_eq!(channel.single_write_and_count(1), 1);
+    /// ```
+    pub fn single_write_and_count(&mut self, event: E) -> usize {
+        let count = self.storage.reader_count();
+        self.storage.single_write(event);
+
+        count
+    }
+
+    /// Write a single event into storage, unless that would require growing
+    /// the buffer because it is full of events some reader hasn't read yet.
+    /// In that case, the event is returned.
+    pub fn try_single_write(&mut self, event: E) -> Result<(), E> {
+        self.storage.try_single_write(event)
+    }
+
+    /// Like `try_single_write`, but if the buffer is full, waits up to
+    /// `timeout` for room before giving up.
+    ///
+    /// Since this borrows the channel mutably, nobody can read while it
+    /// waits. Room is only made by dropping the `ReaderId`s of the readers
+    /// that are behind, e.g. from other threads; when a reader is dropped,
+    /// the write is retried right away.
+    pub fn try_single_write_timeout(&mut self, event: E, timeout: Duration) -> Result<(), E> {
+        self.storage.try_single_write_timeout(event, timeout)
+    }
+
+    /// Write a single event into the priority lane.
+    ///
+    /// `read` returns pending priority events before all other events, which
+    /// lets control events like "shutdown" jump the queue. Of the other read
+    /// methods, only `read_chunked` and `read_smallvec` include the priority
+    /// lane; for the rest, its events stay pending until the next `read`.
+    /// Priority events don't get sequence numbers and don't count towards
+    /// `total_written`.
+    ///
+    /// The lane is a small ring of `PRIORITY_CAPACITY` events. Once it is
+    /// full of events some reader hasn't read yet, the oldest one is dropped
+    /// and counts towards `events_lost_for` of those readers.
+    pub fn single_write_priority(&mut self, event: E) {
+        self.storage.single_write_priority(event);
+    }
+
+    /// Write clones of all events currently stored in `other` into this
+    /// channel, oldest first.
+    ///
+    /// Readers of `other` are not affected.
+    pub fn merge_from(&mut self, other: &EventChannel<E>)
+    where
+        E: Clone,
+    {
+        self.storage.iter_write(other.storage.iter_all().cloned());
+    }
+
+    /// Move all events currently stored in `other` into this channel, oldest
+    /// first.
+    ///
+    /// This leaves `other` empty; all of its readers are advanced past the
+    /// moved events.
+    pub fn merge_from_drain(&mut self, other: &mut EventChannel<E>) {
+        self.storage.iter_write(other.storage.drain());
+    }
+
+    /// Remove all stored events `pred` returns `true` for and return them,
+    /// oldest first.
+    ///
+    /// Readers won't observe the removed events; all other events they
+    /// haven't read yet are still returned by their next read.
+    ///
+    /// Sequence numbers stay contiguous, so the remaining events are
+    /// renumbered: every event written before a removed one moves up by one.
+    /// Sequence numbers obtained earlier, like the range returned by
+    /// `iter_write_seq`, may refer to different events afterwards.
+    /// `Position`s obtained earlier become stale: `read_since` returns all
+    /// stored events for them, and they aren't ordered relative to newer
+    /// ones.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    /// channel.iter_write(0..6);
+    ///
+    /// assert_eq!(channel.drain_matching(|e| e % 3 == 0), vec![0, 3]);
+    /// assert_eq!(
+    ///     channel.read(&mut reader).cloned().collect::<Vec<_>>(),
+    ///     vec![1, 2, 4, 5]
+    /// );
+    /// ```
+    pub fn drain_matching<F>(&mut self, pred: F) -> Vec<E>
+    where
+        F: FnMut(&E) -> bool,
+    {
+        self.storage.drain_matching(pred)
+    }
+
+    /// Drop all but the newest `n` stored events, like for a rolling log
+    /// which keeps only its last lines.
+    ///
+    /// Unlike the capacity, which readers keep growing as needed, this trims
+    /// the history right away, whether readers have read the dropped events
+    /// or not. A reader which hadn't read some of them continues with the
+    /// oldest event left; `poll` reports the dropped ones as missed, and
+    /// `read_checked` returns a `GapError`.
+    ///
+    /// ```
+    /// use shrev::{EventChannel, Poll};
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    /// channel.iter_write(0..100);
+    ///
+    /// channel.retain_last(2);
+    /// match channel.poll(&mut reader) {
+    ///     Poll::Lagged { iter, missed } => {
+    ///         assert_eq!(iter.cloned().collect::<Vec<_>>(), vec![98, 99]);
+    ///         assert_eq!(missed, 98);
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn retain_last(&mut self, n: usize) {
+        self.storage.retain_last(n);
+    }
+
+    /// Iterate over all events currently stored in the channel, oldest first,
+    /// regardless of whether readers have read them already.
+    ///
+    /// No reader is affected.
+    pub fn iter_all(&self) -> EventIterator<'_, E> {
+        self.storage.iter_all()
+    }
+
+    /// Collect references to all events currently stored in the channel,
+    /// oldest first.
+    ///
+    /// This is `iter_all` as a `Vec`, which is convenient for comparisons in
+    /// tests.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::with_capacity(2);
+    /// channel.iter_write(0..3);
+    /// assert_eq!(channel.as_vec(), vec![&1, &2]);
+    /// ```
+    pub fn as_vec(&self) -> Vec<&E> {
+        self.iter_all().collect()
+    }
+
+    /// Clone all events currently stored in the channel, oldest first.
+    ///
+    /// The snapshot doesn't borrow the channel, so it can be sent to other
+    /// threads while writes continue.
+    pub fn snapshot(&self) -> EventSnapshot<E>
+    where
+        E: Clone,
+    {
+        EventSnapshot::new(self.storage.iter_all().cloned().collect())
+    }
+
+    /// Create an independent channel of the same capacity, holding clones of
+    /// all events currently stored in this one.
+    ///
+    /// This is meant for branching: both channels can be written to and read
+    /// from without affecting each other. The fork has no readers, so
+    /// register them with `StartPoint::Oldest` to observe the copied events.
+    /// In the fork, the copied events are numbered from 0, regardless of
+    /// their sequence numbers in this channel.
+    ///
+    /// ```
+    /// use shrev::{EventChannel, RegisterOpts, StartPoint};
+    ///
+    /// let mut channel = EventChannel::new();
+    /// channel.iter_write(0..3);
+    ///
+    /// let mut fork = channel.fork();
+    /// let mut reader = fork.register_reader_opts(RegisterOpts {
+    ///     from: StartPoint::Oldest,
+    /// });
+    /// fork.single_write(10);
+    /// channel.single_write(3);
+    ///
+    /// assert_eq!(fork.read(&mut reader).cloned().collect::<Vec<_>>(), vec![0, 1, 2, 10]);
+    /// ```
+    pub fn fork(&self) -> EventChannel<E>
+    where
+        E: Clone,
+    {
+        let mut fork = EventChannel::with_capacity(self.capacity());
+        fork.iter_write(self.iter_all().cloned());
+
+        fork
+    }
+
+    /// Write all events currently stored in the channel to `w`, oldest first,
+    /// and remove them from the channel. Returns the number of events
+    /// written.
+    ///
+    /// All readers are caught up afterwards, including those which hadn't
+    /// read some of the events yet. If writing fails, the channel is left
+    /// untouched, but some events may have been written to `w` already.
+    ///
+    /// Requires the `serde` feature.
+    ///
+    /// ```
+    /// use shrev::{DumpFormat, EventChannel};
+    ///
+    /// let mut channel = EventChannel::new();
+    /// channel.iter_write(vec![1, 2]);
+    ///
+    /// let mut log = Vec::new();
+    /// assert_eq!(channel.drain_to(&mut log, DumpFormat::JsonLines).unwrap(), 2);
+    /// assert_eq!(log, b"1\n2\n");
+    /// assert!(channel.as_vec().is_empty());
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn drain_to<W>(&mut self, w: &mut W, format: DumpFormat) -> std::io::Result<usize>
+    where
+        W: std::io::Write,
+        E: serde::Serialize,
+    {
+        for event in self.storage.iter_all() {
+            format.write(w, event)?;
+        }
+
+        Ok(self.storage.drain().len())
+    }
+
+    /// Read events written by `drain_to` from `r` and write them into the
+    /// channel in order. Returns the number of events loaded.
+    ///
+    /// Loading stops at the end of `r`, or at the first record which is
+    /// incomplete or can't be deserialized, e.g. because the log was cut off
+    /// by a crash. The events before it are still loaded. Readers registered
+    /// with `StartPoint::Oldest` afterwards observe all loaded events, as
+    /// long as the capacity suffices.
+    ///
+    /// Requires the `serde` feature.
+    ///
+    /// ```
+    /// use shrev::{DumpFormat, EventChannel};
+    ///
+    /// let mut channel = EventChannel::<u32>::new();
+    /// let mut reader = channel.register_reader();
+    ///
+    /// // The last line is incomplete without its newline
+    /// let mut log: &[u8] = b"1\n2\n3";
+    /// assert_eq!(channel.load_from(&mut log, DumpFormat::JsonLines).unwrap(), 2);
+    /// assert_eq!(channel.read(&mut reader).cloned().collect::<Vec<_>>(), vec![1, 2]);
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn load_from<R>(&mut self, r: &mut R, format: DumpFormat) -> std::io::Result<usize>
+    where
+        R: std::io::Read,
+        E: serde::de::DeserializeOwned,
+    {
+        let mut r = std::io::BufReader::new(r);
+        let mut events = Vec::new();
+        while let Some(event) = format.read(&mut r)? {
+            events.push(event);
+        }
+        let loaded = events.len();
+        self.drain_vec_write(&mut events);
+
+        Ok(loaded)
+    }
+
+    /// Drop all events, including those of the priority lane, and move all
+    /// readers past them, for discarding pending work but keeping going.
+    ///
+    /// The capacity and all registered readers are kept. Unlike `reset`,
+    /// this keeps the counters: `total_written` and sequence numbers continue
+    /// where they were, and the events a reader hadn't read yet add to
+    /// `events_lost_for`.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    /// channel.iter_write(0..3);
+    ///
+    /// channel.clear();
+    /// assert_eq!(channel.read(&mut reader).len(), 0);
+    /// assert_eq!(channel.total_written(), 3);
+    /// assert_eq!(channel.events_lost_for(&reader), 3);
+    /// ```
+    pub fn clear(&mut self) {
+        self.storage.clear();
+    }
+
+    /// Drop all events like `clear`, and also reset `total_written` and the
+    /// lost events of every reader to zero, keeping the capacity and all
+    /// registered readers.
+    ///
+    /// Afterwards, the channel behaves like a freshly created one with the
+    /// same readers, which lets it be reused without reallocating the buffer
+    /// or registering the readers again. Sequence numbers start over at 0.
+    pub fn reset(&mut self) {
+        self.storage.reset();
+    }
+
+    /// Read any events that have been written to storage since the last read
+    /// with `reader_id` (or the creation of the `ReaderId`, if it hasn't read
+    /// yet).
+    ///
+    /// Note that this will advance the position of the reader regardless of
+    /// what you do with the iterator. In other words, calling `read`
+    /// without iterating the result won't preserve the events returned. You
+    /// need to iterate all the events as soon as you got them from this
+    /// method. This behavior is equivalent to e.g. `Vec::drain`.
+    ///
+    /// Events written with `single_write_priority` come first, followed by
+    /// the other events in the order they were written.
+    ///
+    /// ## Thread safety
+    ///
+    /// `read` only borrows the channel immutably, so several threads may read
+    /// concurrently, as long as every thread uses its own `ReaderId`. The
+    /// exclusive borrow of `reader_id` guarantees that no two reads ever touch
+    /// the same reader state at the same time.
+    ///
+    /// Writing requires a mutable borrow of the channel, so the borrow checker
+    /// rejects any attempt to write while a read is in progress:
+    ///
+    /// ```compile_fail
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::<u32>::new();
+    /// let mut reader = channel.register_reader();
+    ///
+    /// std::thread::scope(|s| {
+    ///     s.spawn(|| channel.read(&mut reader).count());
+    ///     channel.single_write(1);
+    /// });
+    /// ```
+    pub fn read(&self, reader_id: &mut ReaderId<E>) -> EventIterator<'_, E> {
+        self.storage.read(reader_id)
+    }
+
+    /// Returns the next event `read` would return for `reader_id`, and
+    /// advances it past only that one, or returns `None` if there are no
+    /// new events.
+    ///
+    /// This suits consumers which handle one event at a time, like the
+    /// receiving end of a channel.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    /// channel.iter_write(0..3);
+    ///
+    /// let mut sum = 0;
+    /// while let Some(event) = channel.recv(&mut reader) {
+    ///     sum += event;
+    /// }
+    /// assert_eq!(sum, 3);
+    /// ```
+    pub fn recv(&self, reader_id: &mut ReaderId<E>) -> Option<&E> {
+        self.storage.recv(reader_id)
+    }
+
+    /// Like `recv`, but returns a clone of the event.
+    ///
+    /// Each call only advances `reader_id` by one, without setting up an
+    /// iterator over the pending events.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    /// channel.iter_write(vec![String::from("a"), String::from("b")]);
+    ///
+    /// assert_eq!(channel.recv_owned(&mut reader), Some(String::from("a")));
+    /// assert_eq!(channel.recv_owned(&mut reader), Some(String::from("b")));
+    /// assert_eq!(channel.recv_owned(&mut reader), None);
+    /// ```
+    pub fn recv_owned(&self, reader_id: &mut ReaderId<E>) -> Option<E>
+    where
+        E: Clone,
+    {
+        self.storage.recv(reader_id).cloned()
+    }
+
+    /// Read the pending events like `read`, but newest first.
+    ///
+    /// `reader_id` is still advanced past all of them. Since events of the
+    /// priority lane come first with `read`, they come last here.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    /// channel.iter_write(0..3);
+    ///
+    /// assert_eq!(channel.read_rev(&mut reader).cloned().collect::<Vec<_>>(), vec![2, 1, 0]);
+    /// assert_eq!(channel.read(&mut reader).len(), 0);
+    /// ```
+    pub fn read_rev(&self, reader_id: &mut ReaderId<E>) -> impl ExactSizeIterator<Item = &E> {
+        self.storage.read(reader_id).rev()
+    }
+
+    /// Read the pending events like `read`, yielding `f` applied to each of
+    /// them.
+    ///
+    /// `reader_id` is advanced right away, while `f` is only called as the
+    /// returned iterator is consumed.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    /// channel.iter_write(vec![(1, "a"), (2, "b")]);
+    ///
+    /// let offset = 10;
+    /// let ids = channel.read_map(&mut reader, |&(id, _)| id + offset);
+    /// assert_eq!(ids.len(), 2);
+    /// assert_eq!(ids.collect::<Vec<_>>(), vec![11, 12]);
+    /// assert_eq!(channel.read(&mut reader).len(), 0);
+    /// ```
+    pub fn read_map<'a, T, F>(
+        &'a self,
+        reader_id: &mut ReaderId<E>,
+        f: F,
+    ) -> impl ExactSizeIterator<Item = T> + 'a
+    where
+        F: FnMut(&E) -> T + 'a,
+    {
+        self.storage.read(reader_id).map(f)
+    }
+
+    /// Read the pending events up to and including the first one `stop`
+    /// returns `true` for.
+    ///
+    /// `reader_id` is advanced exactly past that event, so the remaining
+    /// events are returned by the next read. If no event matches, all pending
+    /// events are returned, just like `read` does.
+    pub fn read_until<F>(&self, reader_id: &mut ReaderId<E>, stop: F) -> EventIterator<'_, E>
+    where
+        F: FnMut(&E) -> bool,
+    {
+        self.storage.read_until(reader_id, stop)
+    }
+
+    /// Read at most `max` of the pending events.
+    ///
+    /// `reader_id` is only advanced past the returned events, and
+    /// `remaining_after` tells how many are left for the next read. This way,
+    /// a backlog can be worked off in bounded batches:
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    /// channel.iter_write(0..5);
+    ///
+    /// let mut batches = Vec::new();
+    /// loop {
+    ///     let read = channel.read_with_limit(&mut reader, 2);
+    ///     batches.push(read.iter.cloned().collect::<Vec<_>>());
+    ///     if read.remaining_after == 0 {
+    ///         break;
+    ///     }
+    /// }
+    /// assert_eq!(batches, vec![vec![0, 1], vec![2, 3], vec![4]]);
+    /// ```
+    ///
+    /// Unlike `read`, this doesn't return events of the priority lane.
+    pub fn read_with_limit(&self, reader_id: &mut ReaderId<E>, max: usize) -> LimitedRead<'_, E> {
+        self.storage.read_with_limit(reader_id, max)
+    }
+
+    /// Like `read`, but verifies that `reader_id` doesn't skip any events.
+    ///
+    /// The sequence number of the first pending event is compared with the
+    /// one following the last event `reader_id` observed. If they differ, a
+    /// `GapError` is returned and the reader isn't advanced; use `read` to
+    /// continue reading anyway.
+    ///
+    /// Unless the channel is bounded with `GrowthPolicy::Bounded`, the buffer
+    /// grows instead of overwriting unread events, so this should never fail;
+    /// it mostly serves as a consistency check.
+    pub fn read_checked(
+        &self,
+        reader_id: &mut ReaderId<E>,
+    ) -> Result<EventIterator<'_, E>, GapError> {
+        self.storage.read_checked(reader_id)
+    }
+
+    /// Like `read`, but first verifies that the stored position of
+    /// `reader_id` is consistent with the state of the channel.
+    ///
+    /// This checks internal invariants which should always hold, so an error
+    /// indicates a bug in shrev. It's meant for users who'd rather handle an
+    /// error than rely on these invariants. On error, the reader isn't
+    /// advanced.
+    pub fn read_strict(
+        &self,
+        reader_id: &mut ReaderId<E>,
+    ) -> Result<EventIterator<'_, E>, ConsistencyError> {
+        self.storage.read_strict(reader_id)
+    }
+
+    /// Read the events written since the last read with `reader_id`, telling
+    /// apart whether there were none, some, or whether events were lost
+    /// before the reader could observe them.
+    ///
+    /// ```
+    /// use shrev::{EventChannel, Poll};
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    /// channel.single_write(1);
+    ///
+    /// match channel.poll(&mut reader) {
+    ///     Poll::Empty => {}
+    ///     Poll::Ready(events) => assert_eq!(events.len(), 1),
+    ///     Poll::Lagged { missed, .. } => panic!("lost {} events", missed),
+    /// }
+    /// ```
+    pub fn poll(&self, reader_id: &mut ReaderId<E>) -> Poll<'_, E> {
+        self.storage.poll(reader_id)
+    }
+
+    /// Returns the number of events the next `read` with `reader_id` would
+    /// return.
+    pub fn unread_count(&self, reader_id: &ReaderId<E>) -> usize {
+        self.storage.unread_count(reader_id)
+    }
+
+    /// Returns `true` if `reader_id` has read every event written so far.
+    pub fn is_caught_up(&self, reader_id: &ReaderId<E>) -> bool {
+        self.unread_count(reader_id) == 0
+    }
+
+    /// Returns how long before `now` the first event the next `read` with
+    /// `reader_id` would return was written, or `None` if there is no such
+    /// event.
+    ///
+    /// This tells how far behind a consumer is in time rather than in events,
+    /// e.g. to monitor a latency target. Events of the priority lane are not
+    /// taken into account.
+    ///
+    /// Requires the `timestamps` feature.
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    ///
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    /// assert_eq!(channel.oldest_unread_age(&reader, Instant::now()), None);
+    ///
+    /// channel.single_write(1);
+    /// let later = Instant::now() + Duration::from_millis(200);
+    /// let age = channel.oldest_unread_age(&reader, later).unwrap();
+    /// assert!(age >= Duration::from_millis(200));
+    ///
+    /// channel.read(&mut reader);
+    /// assert_eq!(channel.oldest_unread_age(&reader, later), None);
+    /// ```
+    #[cfg(feature = "timestamps")]
+    pub fn oldest_unread_age(
+        &self,
+        reader_id: &ReaderId<E>,
+        now: std::time::Instant,
+    ) -> Option<Duration> {
+        self.storage.oldest_unread_age(reader_id, now)
+    }
+
+    /// Returns the events the next `read` with `reader_id` would return,
+    /// without advancing it.
+    ///
+    /// Like the other methods which only inspect a reader, this takes the
+    /// `ReaderId` by shared reference; only methods which advance it need a
+    /// mutable one.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    /// channel.iter_write(0..3);
+    ///
+    /// let reader_ref = &reader;
+    /// assert_eq!(channel.peek(reader_ref).cloned().collect::<Vec<_>>(), vec![0, 1, 2]);
+    /// assert_eq!(channel.peek_nth(reader_ref, 1), Some(&1));
+    /// assert!(!channel.is_caught_up(reader_ref));
+    ///
+    /// assert_eq!(channel.read(&mut reader).len(), 3);
+    /// assert!(channel.is_caught_up(&reader));
+    /// ```
+    pub fn peek(&self, reader_id: &ReaderId<E>) -> EventIterator<'_, E> {
+        self.storage.peek(reader_id)
+    }
+
+    /// Returns the `n`th event (counting from 0) the next `read` with
+    /// `reader_id` would return, without advancing it.
+    pub fn peek_nth(&self, reader_id: &ReaderId<E>, n: usize) -> Option<&E> {
+        self.storage.peek(reader_id).nth(n)
+    }
+
+    /// Assert that `reader_id` has read all events written so far.
+    ///
+    /// This is meant for tests; unlike `assert_eq!` on `unread_count`, the
+    /// panic message names the reader and the number of unread events.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `reader_id` has unread events.
+    #[track_caller]
+    pub fn assert_reader_drained(&self, reader_id: &ReaderId<E>) {
+        self.storage.assert_drained(reader_id);
+    }
+
+    /// Returns how many events `reader_id` skipped without reading them,
+    /// over its whole lifetime.
+    ///
+    /// This counts events overwritten because the channel is bounded with
+    /// `GrowthPolicy::Bounded`, dropped by `retain_last` or `clear` and
+    /// skipped with `relieve`. As long as the channel grows as needed and
+    /// those are not used, this stays 0, so it tells whether a consumer drops
+    /// data. `reset` sets it back to 0.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    /// channel.iter_write(0..10);
+    /// channel.retain_last(4);
+    /// channel.read(&mut reader);
+    ///
+    /// assert_eq!(channel.events_lost_for(&reader), 6);
+    /// ```
+    pub fn events_lost_for(&self, reader_id: &ReaderId<E>) -> u64 {
+        self.storage.events_lost_for(reader_id)
+    }
+
+    /// Returns the current position of `reader_id`.
+    ///
+    /// Positions can be compared to find out whether one reader is ahead of
+    /// another one of the same channel.
+    pub fn position_of(&self, reader_id: &ReaderId<E>) -> Position {
+        self.storage.position_of(reader_id)
+    }
+
+    /// Returns how many more events `ahead` has read than `behind`.
+    ///
+    /// Returns 0 if both are at the same position, or if `ahead` is actually
+    /// behind.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if either reader doesn't belong to this channel.
+    pub fn position_delta(&self, ahead: &ReaderId<E>, behind: &ReaderId<E>) -> usize {
+        self.storage.position_delta(ahead, behind)
+    }
+
+    /// Look at the events pending for `reader_id` without advancing it yet.
+    ///
+    /// The returned `PendingRead` can be cloned and inspected freely. To
+    /// actually move the reader past its events, pass it to `commit_read`.
+    /// Since the `PendingRead` borrows the channel, no events can be written
+    /// in between.
+    pub fn begin_read(&self, reader_id: &ReaderId<E>) -> PendingRead<'_, E> {
+        self.storage.begin_read(reader_id)
+    }
+
+    /// Advance `reader_id` past the events of a `PendingRead` obtained from
+    /// `begin_read`.
+    ///
+    /// Returns a `StaleReadError` (and leaves the reader untouched) if
+    /// `reader_id` was advanced by another read after `begin_read`.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `pending` was created for a different reader.
+    pub fn commit_read(
+        &self,
+        reader_id: &mut ReaderId<E>,
+        pending: PendingRead<'_, E>,
+    ) -> Result<(), StaleReadError> {
+        self.storage.commit_read(reader_id, pending)
+    }
+
+    /// Advance `reader_id` past all pending events, returning only the most
+    /// recent one, or `None` if there are no new events.
+    ///
+    /// All intermediate events are discarded for this reader, which is what
+    /// you want for "latest value wins" consumers. Unlike `read().last()`,
+    /// this doesn't walk over the skipped events.
+    pub fn read_latest_only(&self, reader_id: &mut ReaderId<E>) -> Option<&E> {
+        self.storage.read_normal(reader_id).next_back()
+    }
+
+    /// Like `read_latest_only`, but returns a clone of the most recent event.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    /// channel.iter_write(vec![String::from("a"), String::from("b")]);
+    ///
+    /// assert_eq!(channel.read_latest_cloned(&mut reader), Some(String::from("b")));
+    /// assert_eq!(channel.read_latest_cloned(&mut reader), None);
+    /// ```
+    pub fn read_latest_cloned(&self, reader_id: &mut ReaderId<E>) -> Option<E>
+    where
+        E: Clone,
+    {
+        self.read_latest_only(reader_id).cloned()
+    }
+
+    /// Returns the position of a reader which has observed every event
+    /// written so far.
+    ///
+    /// Pass it to `read_since` to start polling for events written from now
+    /// on.
+    pub fn position(&self) -> Position {
+        self.storage.position()
+    }
+
+    /// Returns clones of the events written after `pos`, and the position to
+    /// pass to the next call.
+    ///
+    /// This lets stateless consumers, like long-polling clients of a web
+    /// service, follow the channel without holding a `ReaderId`. Since they
+    /// don't hold back the writer, only the events still stored are
+    /// returned, starting with the oldest one if `pos` is too far behind.
+    /// `set_retention` guarantees how far back that is. The same happens if
+    /// `pos` is stale because `drain_matching` removed events since. Events
+    /// of the priority lane are not included.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `pos` doesn't belong to this channel.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::with_capacity(4);
+    /// channel.set_retention(16);
+    /// let pos = channel.position();
+    ///
+    /// channel.iter_write(0..10);
+    /// let (events, pos) = channel.read_since(pos);
+    /// assert_eq!(events, (0..10).collect::<Vec<_>>());
+    ///
+    /// channel.iter_write(10..40);
+    /// let (events, _) = channel.read_since(pos);
+    /// assert_eq!(events, (24..40).collect::<Vec<_>>());
+    /// ```
+    pub fn read_since(&self, pos: Position) -> (Vec<E>, Position)
+    where
+        E: Clone,
+    {
+        let (iter, pos) = self.storage.read_since(pos);
+
+        (iter.cloned().collect(), pos)
+    }
+
+    /// Keeps at least the newest `window` events stored for `read_since`,
+    /// regardless of the readers.
+    ///
+    /// This grows the buffer to `window` events right away and keeps
+    /// auto-shrinking from going below it. A bounded growth policy takes
+    /// precedence.
+    pub fn set_retention(&mut self, window: usize) {
+        self.storage.set_retention(window);
+    }
+
+    /// Skips all events `reader_id` hasn't read yet and releases memory
+    /// right away, for a consumer which pauses its processing.
+    ///
+    /// Besides moving `reader_id` to the newest event, this frees the slots
+    /// of dropped readers and, if enabled with `set_auto_shrink_ratio`,
+    /// shrinks the buffer once no reader has unread events. Returns the
+    /// number of skipped events and whether the buffer shrank.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::with_capacity(4);
+    /// channel.set_auto_shrink_ratio(Some(0.5));
+    /// let mut paused = channel.register_reader();
+    ///
+    /// channel.iter_write(0..30);
+    /// assert_eq!(channel.capacity(), 32);
+    /// assert_eq!(channel.relieve(&mut paused), (30, true));
+    /// assert_eq!(channel.capacity(), 4);
+    /// ```
+    pub fn relieve(&mut self, reader_id: &mut ReaderId<E>) -> (usize, bool) {
+        self.storage.relieve(reader_id)
+    }
+
+    /// Returns the events with sequence numbers in `start..end`, without
+    /// affecting any reader.
+    ///
+    /// The first event ever written has sequence number 0. Returns `None`
+    /// unless the whole range is still stored in the channel.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::with_capacity(4);
+    /// channel.iter_write(10..16);
+    ///
+    /// let events = channel.events_between(3, 5).unwrap();
+    /// assert_eq!(events.cloned().collect::<Vec<_>>(), vec![13, 14]);
+    /// assert!(channel.events_between(0, 5).is_none());
+    /// ```
+    pub fn events_between(&self, start: u64, end: u64) -> Option<EventIterator<'_, E>> {
+        self.storage.events_between(start, end)
+    }
+
+    /// Move `to` to the position of `from`, so that `to` observes the same
+    /// events with its next read as `from` would.
+    ///
+    /// This allows a new reader to take over where a retiring one stopped.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if either reader doesn't belong to this channel.
+    pub fn copy_position(&mut self, from: &ReaderId<E>, to: &mut ReaderId<E>) {
+        self.storage.copy_position(from, to);
+    }
+
+    /// Read the pending events like `read`, together with the index of the
+    /// slot in the ring buffer each event is stored in.
+    ///
+    /// This is meant for inspecting the buffer layout; the index of an
+    /// event changes when the buffer grows.
+    pub fn read_indexed(
+        &self,
+        reader_id: &mut ReaderId<E>,
+    ) -> impl ExactSizeIterator<Item = (usize, &E)> + DoubleEndedIterator {
+        self.storage.read_normal(reader_id).indexed()
+    }
+
+    /// Read the pending events as pairs of consecutive events, e.g. to
+    /// compute deltas.
+    ///
+    /// If `include_last_read` is `true` and the event `reader_id` read last
+    /// is still stored, the first pair combines it with the first new event,
+    /// so deltas continue seamlessly across reads. Like `read`, this advances
+    /// `reader_id` over all pending events; priority events are left pending.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    /// channel.iter_write(vec![1, 3, 6]);
+    /// let deltas = |(a, b): (&i32, &i32)| b - a;
+    ///
+    /// let first = channel.read_windows(&mut reader, true).map(deltas);
+    /// assert_eq!(first.collect::<Vec<_>>(), vec![2, 3]);
+    ///
+    /// channel.iter_write(vec![10, 15]);
+    /// let second = channel.read_windows(&mut reader, true).map(deltas);
+    /// assert_eq!(second.collect::<Vec<_>>(), vec![4, 5]);
+    /// ```
+    pub fn read_windows(
+        &self,
+        reader_id: &mut ReaderId<E>,
+        include_last_read: bool,
+    ) -> impl Iterator<Item = (&E, &E)> {
+        self.storage.read_windows(reader_id, include_last_read)
+    }
+
+    /// Read the pending events, skipping every event equal to the one before
+    /// it.
+    ///
+    /// This advances `reader_id` over all pending events like `read`, but is
+    /// meant for readers which only act on changes. The reader stores a hash
+    /// of the event `read_dedup` yielded last, and the first pending event is
+    /// compared to it, so repeated events are skipped across reads too, even
+    /// once the earlier event got overwritten. Events are never removed from
+    /// the channel, so other readers are not affected. Priority events are
+    /// left pending.
+    pub fn read_dedup(&self, reader_id: &mut ReaderId<E>) -> impl Iterator<Item = &E>
+    where
+        E: PartialEq + Hash,
+    {
+        self.storage.read_dedup(reader_id)
+    }
+
+    /// Read all pending events of every reader in `readers`, returning clones
+    /// of them, one `Vec` per reader in the same order.
+    ///
+    /// This is meant for tests checking what each reader receives.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut readers = [channel.register_reader(), channel.register_reader()];
+    /// channel.iter_write(0..2);
+    /// channel.read(&mut readers[0]);
+    /// channel.single_write(2);
+    ///
+    /// assert_eq!(
+    ///     channel.drain_all_readers_cloned(&mut readers),
+    ///     vec![vec![2], vec![0, 1, 2]]
+    /// );
+    /// ```
+    pub fn drain_all_readers_cloned(&self, readers: &mut [ReaderId<E>]) -> Vec<Vec<E>>
+    where
+        E: Clone,
+    {
+        readers
+            .iter_mut()
+            .map(|reader| self.read(reader).cloned().collect())
+            .collect()
+    }
+
+    /// Read the pending events like `read`, but as `Cow::Borrowed`.
+    ///
+    /// This is meant for generic code which may or may not need owned
+    /// events; no event is cloned until the caller calls e.g.
+    /// `Cow::into_owned`.
+    pub fn read_cow(
+        &self,
+        reader_id: &mut ReaderId<E>,
+    ) -> impl ExactSizeIterator<Item = Cow<'_, E>> + DoubleEndedIterator
+    where
+        E: Clone,
+    {
+        self.read(reader_id).map(Cow::Borrowed)
+    }
+
+    /// Read the pending events in chunks of at most `chunk` events.
+    ///
+    /// Every chunk is an `EventIterator` of its own, which is handy for
+    /// batched processing. Just like `read`, this advances `reader_id` over
+    /// all pending events right away, so abandoning the iteration early drops
+    /// the remaining events for this reader.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `chunk` is zero.
+    pub fn read_chunked(&self, reader_id: &mut ReaderId<E>, chunk: usize) -> EventChunks<'_, E> {
+        StorageChunks::new(self.storage.read(reader_id), chunk)
+    }
+
+    /// Read clones of the pending events into a `SmallVec`, which only
+    /// allocates if there are more than `N` of them.
+    ///
+    /// Requires the `smallvec` feature.
+    #[cfg(feature = "smallvec")]
+    pub fn read_smallvec<const N: usize>(
+        &self,
+        reader_id: &mut ReaderId<E>,
+    ) -> smallvec::SmallVec<[E; N]>
+    where
+        E: Clone,
+    {
+        // The exact length of the iterator lets `SmallVec` reserve up front
+        self.read(reader_id).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Test {
+        pub id: u32,
+    }
+
+    #[test]
+    fn test_grow() {
+        let mut channel = EventChannel::with_capacity(10);
+
+        let mut reader0 = channel.register_reader();
+        let mut reader1 = channel.register_reader();
+
+        channel.iter_write(vec![1, 2, 3, 4, 5, 6, 7, 8]);
+
+        let data = channel.read(&mut reader0).cloned().collect::<Vec<_>>();
+        assert_eq!(data, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+
+        channel.iter_write(vec![9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22]);
+
+        let data = channel.read(&mut reader0).cloned().collect::<Vec<_>>();
+        assert_eq!(
+            data,
+            vec![9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22]
+        );
+
+        for i in 23..10_000 {
+            channel.single_write(i);
+        }
+
+        let data = channel.read(&mut reader1).cloned().collect::<Vec<_>>();
+        assert_eq!(data, (1..10_000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_read_write() {
+        let mut channel = EventChannel::with_capacity(14);
+
+        let mut reader_id = channel.register_reader();
         let mut reader_id_extra = channel.register_reader();
 
-        channel.single_write(Test { id: 1 });
+        channel.single_write(Test { id: 1 });
+        assert_eq!(
+            vec![Test { id: 1 }],
+            channel.read(&mut reader_id).cloned().collect::<Vec<_>>()
+        );
+        channel.single_write(Test { id: 2 });
+        assert_eq!(
+            vec![Test { id: 2 }],
+            channel.read(&mut reader_id).cloned().collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            vec![Test { id: 1 }, Test { id: 2 }],
+            channel
+                .read(&mut reader_id_extra)
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+
+        channel.single_write(Test { id: 3 });
+        assert_eq!(
+            vec![Test { id: 3 }],
+            channel.read(&mut reader_id).cloned().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![Test { id: 3 }],
+            channel
+                .read(&mut reader_id_extra)
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    // There was previously a case where the tests worked but the example didn't, so
+    // the example was added as a test case.
+    #[test]
+    fn test_example() {
+        let mut channel = EventChannel::new();
+
+        channel.drain_vec_write(&mut vec![TestEvent { data: 1 }, TestEvent { data: 2 }]);
+
+        let mut reader_id = channel.register_reader();
+
+        // Should be empty, because reader was created after the write
+        assert_eq!(
+            Vec::<TestEvent>::default(),
+            channel.read(&mut reader_id).cloned().collect::<Vec<_>>()
+        );
+
+        // Should have data, as a second write was done
+        channel.single_write(TestEvent { data: 5 });
+
+        assert_eq!(
+            vec![TestEvent { data: 5 }],
+            channel.read(&mut reader_id).cloned().collect::<Vec<_>>()
+        );
+
+        // We can also just send in an iterator.
+        channel.iter_write(
+            [TestEvent { data: 8 }, TestEvent { data: 9 }]
+                .iter()
+                .cloned(),
+        );
+
+        assert_eq!(
+            vec![TestEvent { data: 8 }, TestEvent { data: 9 }],
+            channel.read(&mut reader_id).cloned().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_read_chunked() {
+        let mut channel = EventChannel::with_capacity(4);
+        let mut reader = channel.register_reader();
+
+        channel.iter_write(0..3);
+        let _ = channel.read(&mut reader);
+
+        // This write wraps around the end of the buffer
+        channel.iter_write(3..7);
+
+        let chunks = channel.read_chunked(&mut reader, 3);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(
+            chunks
+                .map(|chunk| chunk.cloned().collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            vec![vec![3, 4, 5], vec![6]]
+        );
+        assert_eq!(channel.read_chunked(&mut reader, 3).count(), 0);
+
+        channel.iter_write(7..9);
+        assert_eq!(
+            channel
+                .read_chunked(&mut reader, 16)
+                .map(|chunk| chunk.cloned().collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            vec![vec![7, 8]]
+        );
+    }
+
+    #[test]
+    fn test_with_byte_budget() {
+        let mut channel = EventChannel::<u32>::with_byte_budget(1024);
+        let mut reader = channel.register_reader();
+        assert_eq!(channel.capacity(), 256);
+        channel.iter_write(0..256);
+        assert_eq!(channel.capacity(), 256);
+        assert_eq!(channel.read(&mut reader).len(), 256);
+
+        // Too small budgets still yield a usable channel
+        let channel = EventChannel::<u64>::with_byte_budget(1);
+        assert_eq!(channel.capacity(), 2);
+
+        let channel = EventChannel::<()>::with_byte_budget(0);
+        assert_eq!(channel.capacity(), DEFAULT_CAPACITY);
+    }
+
+    #[test]
+    fn test_read_latest_only() {
+        let mut channel = EventChannel::with_capacity(4);
+        let mut reader = channel.register_reader();
+
+        assert_eq!(channel.read_latest_only(&mut reader), None);
+
+        channel.iter_write(0..3);
+        assert_eq!(channel.read_latest_only(&mut reader), Some(&2));
+        assert_eq!(channel.read_latest_only(&mut reader), None);
+
+        channel.iter_write(3..10);
+        assert_eq!(channel.read_latest_only(&mut reader), Some(&9));
+        assert_eq!(channel.read(&mut reader).len(), 0);
+    }
+
+    #[test]
+    fn test_read_checked() {
+        let mut channel = EventChannel::with_capacity(4);
+        channel.iter_write(0..3);
+        assert_eq!(channel.total_written(), 3);
+
+        let mut reader = channel.register_reader();
+        for i in 0..10 {
+            channel.iter_write(0..i);
+            assert_eq!(channel.read_checked(&mut reader).unwrap().len(), i);
+        }
+        assert_eq!(channel.total_written(), 48);
+        assert_eq!(channel.read_checked(&mut reader).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_with_nonzero_capacity() {
+        let mut channel = EventChannel::with_nonzero_capacity(NonZeroUsize::new(1).unwrap());
+        let mut reader = channel.register_reader();
+        assert_eq!(channel.capacity(), 1);
+
+        channel.iter_write(0..3);
+        assert_eq!(
+            channel.read(&mut reader).cloned().collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_capacity() {
+        EventChannel::<u32>::with_capacity(0);
+    }
+
+    #[test]
+    fn test_begin_commit_read() {
+        let mut channel = EventChannel::with_capacity(4);
+        let mut reader = channel.register_reader();
+        channel.iter_write(0..6);
+
+        let pending = channel.begin_read(&reader);
+        let inspect = pending.clone();
+        assert_eq!(inspect.len(), 6);
+        assert_eq!(
+            inspect.events().cloned().collect::<Vec<_>>(),
+            (0..6).collect::<Vec<_>>()
+        );
+
+        // Nothing happened yet
+        assert_eq!(channel.begin_read(&reader).len(), 6);
+
+        channel.commit_read(&mut reader, pending).unwrap();
+        assert!(channel.begin_read(&reader).is_empty());
+        assert_eq!(channel.read(&mut reader).len(), 0);
+
+        channel.iter_write(6..8);
+        let pending = channel.begin_read(&reader);
+        assert_eq!(channel.read(&mut reader).len(), 2);
+        assert_eq!(
+            channel.commit_read(&mut reader, pending),
+            Err(StaleReadError)
+        );
+    }
+
+    #[test]
+    fn test_max_lag() {
+        let mut channel = EventChannel::with_capacity(8);
+        assert_eq!(channel.max_lag(), 0);
+
+        let mut fast = channel.register_reader();
+        let mut slow = channel.register_reader();
+        assert_eq!(channel.max_lag(), 0);
+
+        channel.iter_write(0..5);
+        let _ = channel.read(&mut fast);
+        assert_eq!(channel.max_lag(), 5);
+
+        // A full lap
+        channel.iter_write(5..8);
+        assert_eq!(channel.max_lag(), 8);
+
+        channel.iter_write(8..12);
+        let _ = channel.read(&mut slow);
+        assert_eq!(channel.max_lag(), 7);
+
+        drop(fast);
+        assert_eq!(channel.max_lag(), 0);
+    }
+
+    #[test]
+    fn test_utilization() {
+        let mut channel = EventChannel::with_capacity(8);
+        assert_eq!(channel.utilization(), 0.0);
+
+        let mut reader = channel.register_reader();
+        channel.iter_write(0..2);
+        assert_eq!(channel.utilization(), 0.25);
+
+        channel.iter_write(2..8);
+        assert_eq!(channel.utilization(), 1.0);
+
+        let _ = channel.read(&mut reader);
+        assert_eq!(channel.utilization(), 0.0);
+
+        channel.iter_write(0..4);
+        drop(reader);
+        assert_eq!(channel.utilization(), 0.0);
+    }
+
+    #[test]
+    fn test_single_write_priority() {
+        let mut channel = EventChannel::with_capacity(4);
+        let mut fast = channel.register_reader();
+        let mut slow = channel.register_reader();
+
+        channel.iter_write(0..3);
+        channel.single_write_priority(10);
+        channel.single_write(3);
+        channel.single_write_priority(11);
         assert_eq!(
-            vec![Test { id: 1 }],
-            channel.read(&mut reader_id).cloned().collect::<Vec<_>>()
+            channel.read(&mut fast).cloned().collect::<Vec<_>>(),
+            [10, 11, 0, 1, 2, 3]
+        );
+        assert_eq!(channel.read(&mut fast).next(), None);
+
+        channel.single_write_priority(12);
+        let late = channel.register_reader();
+        assert_eq!(
+            channel.read(&mut fast).rev().cloned().collect::<Vec<_>>(),
+            [12]
+        );
+        assert_eq!(
+            channel.read(&mut slow).cloned().collect::<Vec<_>>(),
+            [10, 11, 12, 0, 1, 2, 3]
+        );
+
+        drop(late);
+        channel.single_write_priority(13);
+        assert_eq!(channel.read_latest_only(&mut slow), None);
+        assert_eq!(channel.read(&mut slow).cloned().collect::<Vec<_>>(), [13]);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut channel = EventChannel::with_capacity(4);
+        let mut reader = channel.register_reader();
+        let mut other = channel.register_reader();
+        channel.iter_write(0..6);
+        channel.single_write_priority(10);
+        let _ = channel.read(&mut other);
+        let capacity = channel.capacity();
+
+        channel.reset();
+        assert_eq!(channel.total_written(), 0);
+        assert_eq!(channel.capacity(), capacity);
+        assert_eq!(channel.read(&mut reader).next(), None);
+        assert_eq!(channel.position_of(&reader), channel.position_of(&other));
+
+        channel.iter_write(6..8);
+        channel.single_write_priority(11);
+        assert_eq!(
+            channel.read(&mut reader).cloned().collect::<Vec<_>>(),
+            [11, 6, 7]
+        );
+        assert_eq!(
+            channel.read(&mut other).cloned().collect::<Vec<_>>(),
+            [11, 6, 7]
+        );
+        assert_eq!(channel.total_written(), 2);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut channel = EventChannel::with_capacity(4);
+        let mut reader = channel.register_reader();
+        let mut other = channel.register_reader();
+        channel.iter_write(0..6);
+        channel.single_write_priority(10);
+        let _ = channel.read(&mut other);
+        let capacity = channel.capacity();
+
+        channel.clear();
+        assert_eq!(channel.total_written(), 6);
+        assert_eq!(channel.capacity(), capacity);
+        assert_eq!(channel.read(&mut reader).next(), None);
+        assert_eq!(channel.position_of(&reader), channel.position_of(&other));
+        assert_eq!(channel.events_lost_for(&reader), 7);
+        assert_eq!(channel.events_lost_for(&other), 0);
+        assert_eq!(channel.iter_all().len(), 0);
+
+        // Sequence numbers continue after `clear`, but restart after `reset`
+        channel.iter_write(6..8);
+        assert_eq!(channel.events_between(6, 8).unwrap().len(), 2);
+        channel.reset();
+        assert_eq!(channel.events_lost_for(&reader), 0);
+        channel.iter_write(8..10);
+        assert_eq!(channel.events_between(0, 2).unwrap().len(), 2);
+        assert_eq!(
+            channel.read(&mut reader).cloned().collect::<Vec<_>>(),
+            [8, 9]
+        );
+    }
+
+    #[test]
+    fn test_peek() {
+        let mut channel = EventChannel::with_capacity(4);
+        let mut reader = channel.register_reader();
+        assert!(channel.is_caught_up(&reader));
+        assert_eq!(channel.peek_nth(&reader, 0), None);
+
+        channel.iter_write((0..6).map(|id| Test { id }));
+        channel.single_write_priority(Test { id: 10 });
+        let peeked = channel.peek(&reader).cloned().collect::<Vec<_>>();
+        assert_eq!(peeked.len(), channel.unread_count(&reader));
+        assert_eq!(channel.peek_nth(&reader, 0), Some(&Test { id: 10 }));
+        assert_eq!(channel.peek_nth(&reader, 6), Some(&Test { id: 5 }));
+        assert_eq!(channel.peek_nth(&reader, 7), None);
+        assert!(!channel.is_caught_up(&reader));
+
+        assert_eq!(
+            channel.read(&mut reader).cloned().collect::<Vec<_>>(),
+            peeked
+        );
+        assert!(channel.is_caught_up(&reader));
+        assert_eq!(channel.peek(&reader).len(), 0);
+    }
+
+    #[test]
+    fn test_unread_count() {
+        let mut channel = EventChannel::with_capacity(4);
+        let mut reader = channel.register_reader();
+        channel.assert_reader_drained(&reader);
+
+        channel.iter_write(0..6);
+        channel.single_write_priority(6);
+        assert_eq!(channel.unread_count(&reader), 7);
+
+        let _ = channel.read(&mut reader);
+        assert_eq!(channel.unread_count(&reader), 0);
+        channel.assert_reader_drained(&reader);
+    }
+
+    #[test]
+    #[should_panic(expected = "still has 2 unread events")]
+    fn test_assert_reader_drained() {
+        let mut channel = EventChannel::new();
+        let reader = channel.register_reader();
+        channel.iter_write(0..2);
+
+        channel.assert_reader_drained(&reader);
+    }
+
+    #[test]
+    fn test_register_reader_opts() {
+        let mut channel = EventChannel::with_capacity(4);
+        let _keep = channel.register_reader();
+        channel.iter_write(0..3);
+
+        let mut head = channel.register_reader_opts(RegisterOpts::default());
+        let mut oldest = channel.register_reader_opts(RegisterOpts {
+            from: StartPoint::Oldest,
+        });
+        let mut back = channel.register_reader_opts(RegisterOpts {
+            from: StartPoint::Back(1),
+        });
+        channel.single_write(3);
+
+        assert_eq!(channel.read(&mut head).cloned().collect::<Vec<_>>(), [3]);
+        assert_eq!(
+            channel.read(&mut oldest).cloned().collect::<Vec<_>>(),
+            [0, 1, 2, 3]
+        );
+        assert_eq!(channel.read(&mut back).cloned().collect::<Vec<_>>(), [2, 3]);
+    }
+
+    #[test]
+    fn test_position_delta() {
+        let mut channel = EventChannel::with_capacity(4);
+        let mut ahead = channel.register_reader();
+        let mut behind = channel.register_reader();
+        assert_eq!(channel.position_delta(&ahead, &behind), 0);
+
+        channel.iter_write(0..3);
+        let _ = channel.read(&mut ahead);
+        assert_eq!(channel.position_delta(&ahead, &behind), 3);
+        assert_eq!(channel.position_delta(&behind, &ahead), 0);
+
+        // Wraps around and grows
+        channel.iter_write(3..9);
+        let _ = channel.read(&mut ahead);
+        assert_eq!(channel.position_delta(&ahead, &behind), 9);
+
+        let _ = channel.read(&mut behind);
+        assert_eq!(channel.position_delta(&ahead, &behind), 0);
+    }
+
+    #[test]
+    fn test_drain_deque_write() {
+        let mut channel = EventChannel::with_capacity(2);
+        let mut reader = channel.register_reader();
+
+        let mut events = (1..4).collect::<VecDeque<_>>();
+        events.push_front(0);
+        channel.drain_deque_write(&mut events);
+        assert!(events.is_empty());
+        assert_eq!(
+            channel.read(&mut reader).cloned().collect::<Vec<_>>(),
+            [0, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "reader 'physics' still has 1 unread events")]
+    fn test_register_reader_named() {
+        let mut channel = EventChannel::new();
+        let reader = channel.register_reader_named("physics");
+        assert!(format!("{:?}", reader).contains("physics"));
+        assert!(format!("{:?}", channel.clone_reader(&reader)).contains("physics"));
+        channel.single_write(0);
+
+        channel.assert_reader_drained(&reader);
+    }
+
+    #[test]
+    fn test_read_cow() {
+        let mut channel = EventChannel::new();
+        let mut reader = channel.register_reader();
+        channel.iter_write(vec![String::from("a"), String::from("b")]);
+
+        let events = channel.read_cow(&mut reader).collect::<Vec<_>>();
+        assert!(events.iter().all(|e| matches!(e, Cow::Borrowed(_))));
+        let owned = events.into_iter().map(Cow::into_owned).collect::<Vec<_>>();
+        assert_eq!(owned, ["a", "b"]);
+        assert_eq!(channel.read_cow(&mut reader).len(), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_drain_to_length_prefixed() {
+        let mut channel = EventChannel::with_capacity(2);
+        let mut reader = channel.register_reader();
+        channel.iter_write(vec![String::from("a"), String::from("bc")]);
+
+        let mut log = Vec::new();
+        let written = channel.drain_to(&mut log, DumpFormat::LengthPrefixed);
+        assert_eq!(written.unwrap(), 2);
+        assert_eq!(log, b"\x03\0\0\0\"a\"\x04\0\0\0\"bc\"");
+        assert_eq!(channel.read(&mut reader).len(), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_load_from() {
+        for &format in &[DumpFormat::JsonLines, DumpFormat::LengthPrefixed] {
+            let mut channel = EventChannel::new();
+            channel.iter_write(vec![String::from("a"), String::from("bc")]);
+            let mut log = Vec::new();
+            channel.drain_to(&mut log, format).unwrap();
+
+            let mut reader = channel.register_reader();
+            assert_eq!(channel.load_from(&mut &log[..], format).unwrap(), 2);
+            assert_eq!(
+                channel.read(&mut reader).cloned().collect::<Vec<_>>(),
+                ["a", "bc"]
+            );
+
+            // Cut off in the middle of the last record
+            let mut truncated = &log[..log.len() - 2];
+            assert_eq!(channel.load_from(&mut truncated, format).unwrap(), 1);
+            assert_eq!(
+                channel.read(&mut reader).cloned().collect::<Vec<_>>(),
+                ["a"]
+            );
+
+            // Only the very last byte is missing
+            let mut unterminated = &log[..log.len() - 1];
+            assert_eq!(channel.load_from(&mut unterminated, format).unwrap(), 1);
+
+            // Corrupt last record
+            let mut corrupt = log.clone();
+            let at = log.len() - 3;
+            corrupt[at] = b'\\';
+            assert_eq!(channel.load_from(&mut &corrupt[..], format).unwrap(), 1);
+        }
+    }
+
+    #[test]
+    fn test_with_capacity_pow2() {
+        assert_eq!(EventChannel::<u32>::with_capacity_pow2(1).capacity(), 1);
+        assert_eq!(EventChannel::<u32>::with_capacity_pow2(64).capacity(), 64);
+        assert_eq!(EventChannel::<u32>::with_capacity_pow2(100).capacity(), 128);
+    }
+
+    #[test]
+    fn test_try_register_reader() {
+        let mut channel = EventChannel::<Test>::new();
+        let first = channel.register_reader();
+        let second = channel.register_reader();
+        assert_eq!(channel.reader_count(), 2);
+
+        channel.set_max_readers(Some(2));
+        assert_eq!(channel.try_register_reader().unwrap_err().max, 2);
+        // Not limited
+        let third = channel.register_reader();
+        assert_eq!(channel.reader_count(), 3);
+
+        drop(first);
+        drop(second);
+        let fourth = channel.try_register_reader().unwrap();
+        assert_eq!(channel.reader_count(), 2);
+
+        channel.set_max_readers(None);
+        let _fifth = channel.try_register_reader().unwrap();
+        drop((third, fourth));
+        assert_eq!(channel.reader_count(), 1);
+    }
+
+    #[test]
+    fn test_read_with_limit() {
+        let mut channel = EventChannel::with_capacity(4);
+        let mut reader = channel.register_reader();
+        channel.iter_write((0..6).map(|id| Test { id }));
+
+        let read = channel.read_with_limit(&mut reader, 4);
+        assert_eq!(
+            read.iter.map(|t| t.id).collect::<Vec<_>>(),
+            vec![0, 1, 2, 3]
         );
+        assert_eq!(read.remaining_after, 2);
+        // The unread events are kept when writing more
+        channel.iter_write((6..8).map(|id| Test { id }));
+        assert_eq!(channel.read_with_limit(&mut reader, 10).remaining_after, 0);
+
+        let read = channel.read_with_limit(&mut reader, 0);
+        assert_eq!(read.iter.len(), 0);
+        assert_eq!(read.remaining_after, 0);
+    }
+
+    #[test]
+    fn test_batch_writer() {
+        let mut channel = EventChannel::with_capacity(2);
+        let mut reader = channel.register_reader();
+
+        let mut batch = channel.batch_writer();
+        batch.write(Test { id: 0 });
+        batch.write(Test { id: 1 });
+        batch.flush();
+        assert!(batch.is_empty());
+        batch.write(Test { id: 2 });
+        assert_eq!(batch.len(), 1);
+        drop(batch);
+
+        let events = channel.read(&mut reader).map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(events, vec![0, 1, 2]);
+        assert_eq!(channel.capacity(), 4);
+    }
+
+    #[test]
+    fn test_readers_iter() {
+        let mut channel = EventChannel::new();
+        let mut first = channel.register_reader();
+        let second = channel.register_reader();
+        let third = channel.register_reader();
+        channel.iter_write((0..3).map(|id| Test { id }));
+        channel.read(&mut first);
+        let token = second.token();
+        drop(second);
+
+        let infos = channel.readers_iter().collect::<Vec<_>>();
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[0].token, first.token());
+        assert_eq!((infos[0].unread, infos[0].seq), (0, 3));
+        assert_eq!(infos[1].token, third.token());
+        assert_eq!((infos[1].unread, infos[1].seq), (3, 0));
+        assert!(infos.iter().all(|info| info.token != token));
+    }
+
+    #[test]
+    fn test_slowest_reader() {
+        let mut channel = EventChannel::new();
+        assert_eq!(channel.slowest_reader(), None);
+
+        let mut first = channel.register_reader();
+        let mut second = channel.register_reader();
+        channel.iter_write((0..2).map(|id| Test { id }));
+        channel.read(&mut first);
+        assert_eq!(channel.slowest_reader(), Some(second.token()));
+
+        channel.read(&mut second);
         channel.single_write(Test { id: 2 });
+        channel.read(&mut second);
+        assert_eq!(channel.slowest_reader(), Some(first.token()));
+
+        drop(first);
+        assert_eq!(channel.slowest_reader(), Some(second.token()));
+    }
+
+    #[test]
+    fn test_write_observer() {
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut channel = EventChannel::with_capacity(2);
+        let mut reader = channel.register_reader();
+        let log = seen.clone();
+        channel.set_write_observer(Box::new(move |t: &Test| log.lock().unwrap().push(t.id)));
+
+        channel.iter_write((0..3).map(|id| Test { id }));
+        channel.drain_vec_write(&mut vec![Test { id: 3 }]);
+        channel.single_write_priority(Test { id: 4 });
+        assert_eq!(*seen.lock().unwrap(), vec![0, 1, 2, 3, 4]);
+        // Readers still receive every event
+        assert_eq!(channel.read(&mut reader).len(), 5);
+        assert_eq!(channel.capacity(), 4);
+    }
+
+    #[test]
+    fn test_boxed_channel() {
+        let mut channel = BoxedEventChannel::with_capacity(2);
+        let mut reader = channel.register_reader();
+        channel.iter_write((0..3).map(|id| Test { id }));
+        channel.single_write(Test { id: 3 });
+
+        let events = channel.read(&mut reader).map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(events, vec![0, 1, 2, 3]);
+        assert_eq!(channel.read(&mut reader).len(), 0);
+        assert_eq!(channel.as_channel().total_written(), 4);
+    }
+
+    #[test]
+    fn test_fork() {
+        let mut channel = EventChannel::with_capacity(4);
+        let mut reader = channel.register_reader();
+        channel.iter_write((0..6).map(|id| Test { id }));
+
+        let mut fork = channel.fork();
+        assert_eq!(fork.capacity(), channel.capacity());
+        assert_eq!(fork.total_written(), 6);
+        assert!(!fork.would_write());
+        // Readers of the original are unaffected
+        assert_eq!(channel.read(&mut reader).len(), 6);
+
+        let mut fork_reader = fork.register_reader_back(6);
+        fork.single_write(Test { id: 6 });
+        let events = fork
+            .read(&mut fork_reader)
+            .map(|t| t.id)
+            .collect::<Vec<_>>();
+        assert_eq!(events, (0..7).collect::<Vec<_>>());
+        assert_eq!(channel.read(&mut reader).len(), 0);
+    }
+
+    #[test]
+    fn test_available() {
+        let mut channel = EventChannel::with_capacity(4);
+        assert_eq!(channel.available(), 4);
+        let mut reader = channel.register_reader();
+        let lagging = channel.register_reader();
+
+        for i in 0..4 {
+            assert_eq!(channel.available(), 4 - i as usize);
+            channel.single_write(Test { id: i });
+        }
+        // The next write would grow the channel instead
+        assert!(!channel.is_full());
+        channel.set_growth_policy(GrowthPolicy::Bounded { max: 4 });
+        assert!(channel.is_full());
+        channel.set_growth_policy(GrowthPolicy::Double);
+        channel.read(&mut reader);
+        assert_eq!(channel.available(), 0);
+
+        drop(lagging);
+        assert_eq!(channel.available(), 4);
+        channel.iter_write((0..4).map(|id| Test { id }));
+        assert_eq!(channel.capacity(), 4);
+    }
+
+    #[test]
+    fn test_write_dedup_by_key() {
+        let mut channel = EventChannel::new();
+        let mut reader = channel.register_reader();
+
+        let ids = [3, 1, 3, 2, 1, 3];
+        channel.iter_write_dedup_by_key(ids.iter().map(|&id| Test { id }), |e| e.id);
         assert_eq!(
-            vec![Test { id: 2 }],
-            channel.read(&mut reader_id).cloned().collect::<Vec<_>>()
+            channel.read(&mut reader).map(|e| e.id).collect::<Vec<_>>(),
+            vec![2, 1, 3]
         );
 
+        // Previous batches are not considered
+        channel.iter_write_dedup_by_key(vec![Test { id: 3 }, Test { id: 4 }], |e| e.id);
+        channel.iter_write_dedup_by_key(Vec::new(), |e| e.id);
         assert_eq!(
-            vec![Test { id: 1 }, Test { id: 2 }],
-            channel
-                .read(&mut reader_id_extra)
-                .cloned()
-                .collect::<Vec<_>>()
+            channel.read(&mut reader).map(|e| e.id).collect::<Vec<_>>(),
+            vec![3, 4]
         );
+    }
 
+    #[test]
+    fn test_iter_write_seq() {
+        let mut channel = EventChannel::with_capacity(4);
+        assert_eq!(channel.iter_write_seq((0..3).map(|id| Test { id })), 0..3);
+        assert_eq!(channel.iter_write_seq(Vec::<Test>::new()), 3..3);
         channel.single_write(Test { id: 3 });
+        assert_eq!(channel.iter_write_seq((4..10).map(|id| Test { id })), 4..10);
+    }
+
+    #[test]
+    fn test_clone_write() {
+        let mut channel = EventChannel::with_capacity(2);
+        let mut reader = channel.register_reader();
+
+        let events = [Test { id: 1 }, Test { id: 2 }, Test { id: 3 }];
+        channel.clone_write(&events);
+        channel.clone_write(&[]);
         assert_eq!(
-            vec![Test { id: 3 }],
-            channel.read(&mut reader_id).cloned().collect::<Vec<_>>()
+            channel.read(&mut reader).cloned().collect::<Vec<_>>(),
+            events
         );
+    }
+
+    #[test]
+    fn test_position_of() {
+        let mut channel = EventChannel::with_capacity(4);
+        let mut a = channel.register_reader();
+        let mut b = channel.register_reader();
+        assert_eq!(channel.position_of(&a), channel.position_of(&b));
+
+        channel.iter_write(0..10);
+        let _ = channel.read(&mut a);
+        assert!(channel.position_of(&a) > channel.position_of(&b));
+
+        channel.iter_write(10..12);
+        let _ = channel.read(&mut b);
+        assert!(channel.position_of(&a) < channel.position_of(&b));
+
+        let mut other = EventChannel::<i32>::new();
+        let c = other.register_reader();
+        let (a, c) = (channel.position_of(&a), other.position_of(&c));
+        assert!(a != c);
+        assert_eq!(a.partial_cmp(&c), None);
+    }
+
+    #[test]
+    fn test_merge_from() {
+        let mut central = EventChannel::with_capacity(4);
+        let mut central_reader = central.register_reader();
+
+        let mut first = EventChannel::with_capacity(4);
+        let mut first_reader = first.register_reader();
+        first.iter_write(0..3);
+
+        let mut second = EventChannel::with_capacity(4);
+        let mut second_reader = second.register_reader();
+        second.iter_write(3..6);
+
+        central.merge_from(&first);
+        central.merge_from_drain(&mut second);
         assert_eq!(
-            vec![Test { id: 3 }],
+            central
+                .read(&mut central_reader)
+                .cloned()
+                .collect::<Vec<_>>(),
+            (0..6).collect::<Vec<_>>()
+        );
+
+        assert_eq!(first.read(&mut first_reader).len(), 3);
+        assert_eq!(second.read(&mut second_reader).len(), 0);
+
+        // Nothing left to merge
+        central.merge_from_drain(&mut second);
+        assert_eq!(central.read(&mut central_reader).len(), 0);
+    }
+
+    #[test]
+    fn test_read_until() {
+        let mut channel = EventChannel::with_capacity(4);
+        let mut reader = channel.register_reader();
+        let mut other = channel.register_reader();
+
+        let read_until = |channel: &EventChannel<i32>, reader: &mut ReaderId<i32>| {
             channel
-                .read(&mut reader_id_extra)
+                .read_until(reader, |&e| e % 5 == 0)
                 .cloned()
                 .collect::<Vec<_>>()
+        };
+
+        channel.iter_write(1..8);
+        assert_eq!(read_until(&channel, &mut reader), vec![1, 2, 3, 4, 5]);
+        assert!(channel.position_of(&reader) > channel.position_of(&other));
+        // Leaves the rest in place, even if the buffer has to grow
+        channel.iter_write(8..12);
+        assert_eq!(read_until(&channel, &mut reader), vec![6, 7, 8, 9, 10]);
+        assert_eq!(read_until(&channel, &mut reader), vec![11]);
+        assert_eq!(read_until(&channel, &mut reader), Vec::<i32>::new());
+
+        channel.iter_write(vec![15, 16]);
+        assert_eq!(read_until(&channel, &mut reader), vec![15]);
+        assert_eq!(read_until(&channel, &mut reader), vec![16]);
+
+        assert_eq!(
+            channel.read(&mut other).cloned().collect::<Vec<_>>(),
+            (1..12).chain(15..17).collect::<Vec<_>>()
         );
     }
 
-    // There was previously a case where the tests worked but the example didn't, so
-    // the example was added as a test case.
     #[test]
-    fn test_example() {
-        let mut channel = EventChannel::new();
+    fn test_add_assign() {
+        let mut channel = EventChannel::with_capacity(2);
+        let mut reader = channel.register_reader();
 
-        channel.drain_vec_write(&mut vec![TestEvent { data: 1 }, TestEvent { data: 2 }]);
+        let mut events = vec![1, 2, 3];
+        channel += &mut events;
+        assert!(events.is_empty());
+        channel += &mut events;
 
-        let mut reader_id = channel.register_reader();
+        assert_eq!(
+            channel.read(&mut reader).cloned().collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+    }
 
-        // Should be empty, because reader was created after the write
+    #[test]
+    fn test_snapshot() {
+        let mut channel = EventChannel::with_capacity(4);
+        assert!(channel.snapshot().is_empty());
+
+        let _reader = channel.register_reader();
+        channel.iter_write(0..3);
+        let snapshot = channel.snapshot();
+        channel.iter_write(3..6);
+
+        let copy = snapshot.clone();
+        let handle = std::thread::spawn(move || copy.to_vec());
+        assert_eq!(handle.join().unwrap(), vec![0, 1, 2]);
+        assert_eq!(&*channel.snapshot(), &[0, 1, 2, 3, 4, 5][..]);
+    }
+
+    #[cfg(feature = "smallvec")]
+    #[test]
+    fn test_read_smallvec() {
+        let mut channel = EventChannel::with_capacity(4);
+        let mut reader = channel.register_reader();
+
+        channel.iter_write(0..3);
+        let events = channel.read_smallvec::<4>(&mut reader);
+        assert_eq!(&events[..], &[0, 1, 2]);
+        assert!(!events.spilled());
+
+        channel.iter_write(3..8);
+        let events = channel.read_smallvec::<4>(&mut reader);
+        assert_eq!(&events[..], &[3, 4, 5, 6, 7]);
+        assert!(events.spilled());
+    }
+
+    #[test]
+    fn test_copy_position() {
+        let mut channel = EventChannel::with_capacity(4);
+        let mut retiring = channel.register_reader();
+        channel.iter_write(0..3);
+        channel
+            .read_until(&mut retiring, |&e| e == 1)
+            .for_each(drop);
+        channel.iter_write(3..5);
+
+        let mut successor = channel.register_reader();
+        channel.copy_position(&retiring, &mut successor);
         assert_eq!(
-            Vec::<TestEvent>::default(),
-            channel.read(&mut reader_id).cloned().collect::<Vec<_>>()
+            channel.position_of(&successor),
+            channel.position_of(&retiring)
         );
+        drop(retiring);
 
-        // Should have data, as a second write was done
-        channel.single_write(TestEvent { data: 5 });
+        channel.iter_write(5..10);
+        assert_eq!(
+            channel.read(&mut successor).cloned().collect::<Vec<_>>(),
+            (2..10).collect::<Vec<_>>()
+        );
+    }
 
+    #[test]
+    fn test_read_indexed() {
+        let mut channel = EventChannel::with_capacity(2);
+        let mut reader = channel.register_reader();
+        channel.iter_write(0..3);
+
+        let events = channel.read_indexed(&mut reader).collect::<Vec<_>>();
+        let mut indices = events.iter().map(|&(i, _)| i).collect::<Vec<_>>();
         assert_eq!(
-            vec![TestEvent { data: 5 }],
-            channel.read(&mut reader_id).cloned().collect::<Vec<_>>()
+            events.iter().map(|&(_, &e)| e).collect::<Vec<_>>(),
+            vec![0, 1, 2]
         );
+        indices.sort_unstable();
+        indices.dedup();
+        assert_eq!(indices.len(), 3);
+        assert!(indices.iter().all(|&i| i < channel.capacity()));
+    }
 
-        // We can also just send in an iterator.
-        channel.iter_write(
-            [TestEvent { data: 8 }, TestEvent { data: 9 }]
-                .iter()
-                .cloned(),
+    #[test]
+    fn test_clone_reader() {
+        let mut channel = EventChannel::with_capacity(4);
+        let mut reader = channel.register_reader();
+        channel.iter_write(0..3);
+        channel.read_until(&mut reader, |&e| e == 0).for_each(drop);
+
+        let mut clone = channel.clone_reader(&reader);
+        assert_eq!(channel.position_of(&clone), channel.position_of(&reader));
+        channel.iter_write(3..6);
+        assert_eq!(
+            channel.read(&mut reader).cloned().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5]
         );
 
+        // The clone keeps its events alive on its own
+        drop(reader);
+        channel.iter_write(6..10);
         assert_eq!(
-            vec![TestEvent { data: 8 }, TestEvent { data: 9 }],
-            channel.read(&mut reader_id).cloned().collect::<Vec<_>>()
+            channel.read(&mut clone).cloned().collect::<Vec<_>>(),
+            (1..10).collect::<Vec<_>>()
+        );
+    }
+
+    #[cfg(feature = "allocator-api2")]
+    #[test]
+    fn test_with_capacity_in() {
+        use allocator_api2::alloc::{AllocError, Allocator, Global, Layout};
+        use std::{
+            ptr::NonNull,
+            sync::{
+                Arc,
+                atomic::{AtomicUsize, Ordering},
+            },
+        };
+
+        struct Counting(Arc<AtomicUsize>);
+
+        unsafe impl Allocator for Counting {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                self.0.fetch_add(1, Ordering::Relaxed);
+                Global.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                Global.deallocate(ptr, layout)
+            }
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let mut channel = EventChannel::with_capacity_in(2, Counting(count.clone()));
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+
+        let mut reader = channel.register_reader();
+        channel.iter_write(0..5);
+        assert!(count.load(Ordering::Relaxed) > 1);
+        assert_eq!(
+            channel.read(&mut reader).cloned().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn test_try_single_write() {
+        let mut channel = EventChannel::with_capacity(2);
+        assert_eq!(channel.try_single_write(0), Ok(()));
+
+        let mut reader = channel.register_reader();
+        let lagging = channel.register_reader();
+        channel.iter_write(1..3);
+        channel.read(&mut reader).for_each(drop);
+        assert_eq!(channel.try_single_write(3), Err(3));
+        assert_eq!(
+            channel.try_single_write_timeout(3, Duration::from_millis(10)),
+            Err(3)
+        );
+        assert_eq!(channel.capacity(), 2);
+
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            drop(lagging);
+        });
+        assert_eq!(
+            channel.try_single_write_timeout(3, Duration::from_secs(10)),
+            Ok(())
+        );
+        handle.join().unwrap();
+        assert_eq!(
+            channel.read(&mut reader).cloned().collect::<Vec<_>>(),
+            vec![3]
+        );
+
+        // Timeouts past what `Instant` can represent wait without a deadline
+        let lagging = channel.register_reader();
+        channel.iter_write(4..6);
+        channel.read(&mut reader).for_each(drop);
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            drop(lagging);
+        });
+        assert_eq!(channel.try_single_write_timeout(6, Duration::MAX), Ok(()));
+        handle.join().unwrap();
+        assert_eq!(
+            channel.read(&mut reader).cloned().collect::<Vec<_>>(),
+            vec![6]
         );
     }
 
@@ -0,0 +1,77 @@
+//! An event channel for events which can't be shared between threads.
+
+use crate::{DEFAULT_CAPACITY, EventIterator, ReaderId, storage::RingBuffer};
+
+/// An `EventChannel` for events which are not `Send` or `Sync`, like ones
+/// holding a `Cell` or an `Rc`.
+///
+/// Events are stored in the same ring buffer as in `EventChannel`, which is
+/// only `Send` and `Sync` if the events are. So unlike `EventChannel`, a
+/// `LocalEventChannel` generally stays on the thread that created it.
+///
+/// Requires the `single-thread` feature.
+///
+/// ## Examples
+///
+/// ```
+/// use std::{cell::Cell, rc::Rc};
+///
+/// use shrev::LocalEventChannel;
+///
+/// let counter = Rc::new(Cell::new(0));
+/// let mut channel = LocalEventChannel::new();
+/// let mut reader = channel.register_reader();
+///
+/// channel.single_write(counter.clone());
+/// for event in channel.read(&mut reader) {
+///     event.set(event.get() + 1);
+/// }
+/// assert_eq!(counter.get(), 1);
+/// ```
+#[derive(Debug)]
+pub struct LocalEventChannel<E: 'static> {
+    storage: RingBuffer<E>,
+}
+
+impl<E: 'static> Default for LocalEventChannel<E> {
+    fn default() -> Self {
+        LocalEventChannel::with_capacity(DEFAULT_CAPACITY)
+    }
+}
+
+impl<E: 'static> LocalEventChannel<E> {
+    channel_basics!("LocalEventChannel", RingBuffer::with_nonzero);
+
+    /// Register a new reader, see `EventChannel::register_reader`.
+    pub fn register_reader(&mut self) -> ReaderId<E> {
+        self.storage.new_reader_id()
+    }
+
+    storage_write_read!();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+
+    #[test]
+    fn test_non_sync_events() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut channel = LocalEventChannel::<Rc<RefCell<Vec<u32>>>>::with_capacity(2);
+        let mut first = channel.register_reader();
+        let mut second = channel.register_reader();
+
+        channel.iter_write((0..3).map(|_| log.clone()));
+        for (i, event) in channel.read(&mut first).enumerate() {
+            event.borrow_mut().push(i as u32);
+        }
+        assert_eq!(channel.read(&mut second).len(), 3);
+        assert_eq!(*log.borrow(), vec![0, 1, 2]);
+
+        drop((first, second));
+        drop(channel);
+        assert_eq!(Rc::strong_count(&log), 1);
+    }
+}
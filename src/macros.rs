@@ -0,0 +1,72 @@
+//! Methods shared by the channel types wrapping a ring buffer.
+
+/// Implements the constructors and the capacity queries of a channel type
+/// `$name` which keeps its buffer in a `storage` field, created with
+/// `$with_nonzero`. The type has to implement `Default`.
+macro_rules! channel_basics {
+    ($name:literal, $with_nonzero:path) => {
+        #[doc = concat!("Create a new `", $name, "` with a default size of 64, see")]
+        /// `DEFAULT_CAPACITY`.
+        pub fn new() -> Self {
+            Default::default()
+        }
+
+        #[doc = concat!("Create a new `", $name, "` with the given starting capacity.")]
+        ///
+        /// Panics if `size` is zero.
+        pub fn with_capacity(size: usize) -> Self {
+            Self::with_nonzero_capacity(
+                std::num::NonZeroUsize::new(size)
+                    .expect("`EventChannel` capacity must be non-zero"),
+            )
+        }
+
+        #[doc = concat!("Create a new `", $name, "` with the given starting capacity.")]
+        pub fn with_nonzero_capacity(size: std::num::NonZeroUsize) -> Self {
+            Self {
+                storage: $with_nonzero(size),
+            }
+        }
+
+        /// Returns the number of events the buffer can currently hold.
+        pub fn capacity(&self) -> usize {
+            self.storage.capacity()
+        }
+
+        /// Returns `true` if any reader would observe an additional event.
+        pub fn would_write(&mut self) -> bool {
+            self.storage.would_write()
+        }
+    };
+}
+
+/// Implements writing events of type `E` to the `RingBuffer` in the
+/// `storage` field, and reading them back.
+macro_rules! storage_write_read {
+    () => {
+        /// Write an iterator of events into storage
+        pub fn iter_write<I>(&mut self, iter: I)
+        where
+            I: IntoIterator<Item = E>,
+            I::IntoIter: ExactSizeIterator,
+        {
+            self.storage.iter_write(iter);
+        }
+
+        /// Drain a vector of events into storage.
+        pub fn drain_vec_write(&mut self, events: &mut Vec<E>) {
+            self.storage.drain_vec_write(events);
+        }
+
+        /// Write a single event into storage.
+        pub fn single_write(&mut self, event: E) {
+            self.storage.single_write(event);
+        }
+
+        /// Read any events that have been written to storage since the last read
+        /// with `reader_id`, see `EventChannel::read`.
+        pub fn read(&self, reader_id: &mut ReaderId<E>) -> EventIterator<'_, E> {
+            self.storage.read(reader_id)
+        }
+    };
+}
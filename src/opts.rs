@@ -0,0 +1,98 @@
+//! Options for registering readers and growing the buffer.
+
+/// Where a newly registered reader starts reading.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StartPoint {
+    /// Only observe events written after the registration.
+    #[default]
+    Head,
+    /// Observe all events still stored in the channel.
+    Oldest,
+    /// Observe the last `n` events still stored in the channel, or all of
+    /// them if there are fewer.
+    Back(usize),
+}
+
+/// Options for `EventChannel::register_reader_opts`.
+///
+/// The default options are those `register_reader` uses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RegisterOpts {
+    /// Where the new reader starts reading.
+    pub from: StartPoint,
+}
+
+/// How an `EventChannel` grows when a reader would miss events otherwise,
+/// see `EventChannel::set_growth_policy`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GrowthPolicy {
+    /// Double the capacity as often as needed.
+    #[default]
+    Double,
+    /// Multiply the capacity by the factor as often as needed, rounding up.
+    /// The factor has to be greater than 1.
+    Factor(f64),
+    /// Add the increment to the capacity as often as needed. The increment
+    /// has to be non-zero.
+    FixedIncrement(usize),
+    /// Double the capacity like `Double`, but never beyond `max`. Once the
+    /// buffer can't grow anymore, the oldest events are overwritten even if
+    /// some reader hasn't read them yet; `EventChannel::poll` reports those
+    /// as missed.
+    Bounded {
+        /// The capacity the buffer doesn't grow beyond.
+        max: usize,
+    },
+}
+
+impl GrowthPolicy {
+    /// Panics if the parameters of the policy are invalid.
+    pub(crate) fn validate(self) {
+        match self {
+            GrowthPolicy::Factor(factor) => assert!(
+                factor > 1.0 && factor.is_finite(),
+                "growth factor must be finite and greater than 1, got {}",
+                factor
+            ),
+            GrowthPolicy::FixedIncrement(increment) => {
+                assert!(increment > 0, "growth increment must be non-zero")
+            }
+            GrowthPolicy::Double | GrowthPolicy::Bounded { .. } => {}
+        }
+    }
+
+    /// Returns the capacity to grow a buffer of capacity `size` to, which
+    /// needs room for at least `min_size` elements. For `Bounded`, the
+    /// result may be less than `min_size` and even `size`.
+    pub(crate) fn next_size(self, size: usize, min_size: usize) -> usize {
+        let double = || {
+            // Make sure size' = 2^n * size. Only the final size is
+            // allocated, so a large write grows the buffer in one step, to
+            // less than twice `min_size`.
+            let mut new = 2 * size;
+            while new < min_size {
+                new *= 2;
+            }
+
+            new
+        };
+
+        match self {
+            GrowthPolicy::Double => double(),
+            GrowthPolicy::Factor(factor) => {
+                let mut new = size;
+                while new < min_size {
+                    new = ((new as f64 * factor).ceil() as usize).max(new + 1);
+                }
+
+                new
+            }
+            GrowthPolicy::FixedIncrement(increment) => {
+                let steps = min_size.saturating_sub(size).div_ceil(increment);
+
+                size + steps.max(1) * increment
+            }
+            GrowthPolicy::Bounded { max } => double().min(max),
+        }
+    }
+}
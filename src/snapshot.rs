@@ -0,0 +1,35 @@
+//! Owned copies of the events held by an `EventChannel`.
+
+use std::{ops::Deref, sync::Arc};
+
+/// The events of a channel at the time `EventChannel::snapshot` was called,
+/// ordered from oldest to newest.
+///
+/// Cloning a snapshot only bumps a reference count; later writes to the
+/// channel don't affect it.
+#[derive(Debug)]
+pub struct EventSnapshot<E> {
+    events: Arc<[E]>,
+}
+
+impl<E> EventSnapshot<E> {
+    pub(crate) fn new(events: Arc<[E]>) -> Self {
+        EventSnapshot { events }
+    }
+}
+
+impl<E> Clone for EventSnapshot<E> {
+    fn clone(&self) -> Self {
+        EventSnapshot {
+            events: self.events.clone(),
+        }
+    }
+}
+
+impl<E> Deref for EventSnapshot<E> {
+    type Target = [E];
+
+    fn deref(&self) -> &[E] {
+        &self.events
+    }
+}
@@ -0,0 +1,116 @@
+//! Producer and consumer handles sharing an `EventChannel`.
+
+use std::sync::{Arc, RwLock, RwLockWriteGuard};
+
+use crate::{Event, EventChannel, EventIterator, ReaderId};
+
+type Shared<E> = Arc<RwLock<EventChannel<E>>>;
+
+/// The writing half of a split `EventChannel`, see `EventChannel::split`.
+#[derive(Debug)]
+pub struct Producer<E> {
+    channel: Shared<E>,
+}
+
+impl<E> Producer<E>
+where
+    E: Event,
+{
+    /// Write an iterator of events into the channel.
+    pub fn iter_write<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = E>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        write(&self.channel).iter_write(iter);
+    }
+
+    /// Drain a vector of events into the channel.
+    pub fn drain_vec_write(&mut self, events: &mut Vec<E>) {
+        write(&self.channel).drain_vec_write(events);
+    }
+
+    /// Write a single event into the channel.
+    pub fn single_write(&mut self, event: E) {
+        write(&self.channel).single_write(event);
+    }
+
+    /// Returns `true` if any consumer would observe an additional event.
+    pub fn would_write(&mut self) -> bool {
+        write(&self.channel).would_write()
+    }
+}
+
+/// A reader bundled with a handle to the channel it reads from, see
+/// `EventChannel::split`.
+#[derive(Debug)]
+pub struct Consumer<E: 'static> {
+    channel: Shared<E>,
+    reader: ReaderId<E>,
+}
+
+impl<E> Consumer<E>
+where
+    E: Event,
+{
+    /// Read all events written since the last read of this consumer, passing
+    /// them to `f`.
+    ///
+    /// The channel is locked for the duration of `f`, which means the
+    /// `Producer` can't write in the meantime.
+    pub fn read<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(EventIterator<'_, E>) -> R,
+    {
+        let channel = self.channel.read().expect("`EventChannel` lock poisoned");
+
+        f(channel.read(&mut self.reader))
+    }
+}
+
+impl<E> EventChannel<E>
+where
+    E: Event,
+{
+    /// Split this channel into a `Producer`, which does all the writing, and
+    /// a function creating new `Consumer`s.
+    ///
+    /// Every `Consumer` owns a reader and a handle to the channel, so it can
+    /// be handed to a subsystem on its own.
+    ///
+    /// Existing readers of this channel can no longer read after the split,
+    /// but they stay registered until dropped: they keep the events they
+    /// haven't read alive, so the buffer grows instead of overwriting them.
+    /// Drop them before splitting the channel.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let (mut producer, consumer) = EventChannel::new().split();
+    /// let mut consumer = consumer();
+    ///
+    /// producer.iter_write(vec![1, 2, 3]);
+    ///
+    /// let sum = consumer.read(|events| events.sum::<i32>());
+    /// assert_eq!(sum, 6);
+    /// ```
+    pub fn split(self) -> (Producer<E>, impl Fn() -> Consumer<E> + Clone + Send + Sync) {
+        let channel = Arc::new(RwLock::new(self));
+        let producer = Producer {
+            channel: channel.clone(),
+        };
+
+        let consumer = move || Consumer {
+            reader: write(&channel).register_reader(),
+            channel: channel.clone(),
+        };
+
+        (producer, consumer)
+    }
+}
+
+fn write<E>(channel: &RwLock<EventChannel<E>>) -> RwLockWriteGuard<'_, EventChannel<E>> {
+    channel.write().expect("`EventChannel` lock poisoned")
+}
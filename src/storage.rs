@@ -1,33 +1,64 @@
 //! Ring buffer implementation, that does immutable reads.
 
 use std::{
-    cell::UnsafeCell,
+    cmp::Ordering,
+    collections::hash_map::DefaultHasher,
     fmt,
+    hash::{Hash, Hasher},
     marker::PhantomData,
-    num::Wrapping,
+    mem::{self, MaybeUninit},
+    num::NonZeroUsize,
     ops::{Add, AddAssign, Sub, SubAssign},
-    ptr,
-    sync::mpsc::{self, Receiver, Sender},
+    ptr, slice,
+    sync::atomic::{AtomicUsize, Ordering as AtomicOrdering},
+    time::{Duration, Instant},
 };
 
-use crate::util::{InstanceId, NoSharedAccess, Reference};
+#[cfg(feature = "check_reentrancy")]
+use crate::util::WriteFlag;
+use crate::{
+    PRIORITY_CAPACITY,
+    error::{ConsistencyError, GapError, StaleReadError},
+    opts::GrowthPolicy,
+    util::{
+        Buf, InstanceId, NoSharedAccess, OnDrop, Reference, UnsafeCell, buf_like,
+        mpsc::{self, Receiver, Sender},
+        new_buf,
+    },
+};
+#[cfg(feature = "timestamps")]
+use std::collections::VecDeque;
 use std::fmt::Debug;
 
 #[derive(Clone, Copy, Debug)]
 struct CircularIndex {
     index: usize,
     size: usize,
+    /// `size - 1` if `size` is a power of two, so wrapping is a cheap bitwise
+    /// and. Otherwise `!0`, and wrapping uses `%` instead.
+    mask: usize,
 }
 
 impl CircularIndex {
     fn new(index: usize, size: usize) -> Self {
-        CircularIndex { index, size }
+        let mask = match size.is_power_of_two() {
+            true => size - 1,
+            false => !0,
+        };
+
+        CircularIndex { index, size, mask }
     }
 
     fn at_end(size: usize) -> Self {
-        CircularIndex {
-            index: size - 1,
-            size,
+        CircularIndex::new(size - 1, size)
+    }
+
+    /// Wraps `index` into `0..size`.
+    #[inline(always)]
+    fn wrap(&self, index: usize) -> usize {
+        match self.mask {
+            mask if mask != !0 => index & mask,
+            _ => index % self.size,
         }
     }
 
@@ -87,7 +118,7 @@ impl Add<usize> for CircularIndex {
     type Output = usize;
 
     fn add(self, rhs: usize) -> usize {
-        (self.index + rhs) % self.size
+        self.wrap(self.index + rhs)
     }
 }
 
@@ -101,7 +132,7 @@ impl Sub<usize> for CircularIndex {
     type Output = usize;
 
     fn sub(self, rhs: usize) -> usize {
-        (self.size - rhs + self.index) % self.size
+        self.wrap(self.size - rhs + self.index)
     }
 }
 
@@ -112,15 +143,23 @@ impl SubAssign<usize> for CircularIndex {
 }
 
 struct Data<T> {
-    data: Vec<T>,
+    data: Buf<T>,
     uninitialized: usize,
+    /// Zero the bytes of slots whose element was dropped or moved out
+    scrub: bool,
 }
 
 impl<T> Data<T> {
     fn new(size: usize) -> Self {
+        Self::with_buf(new_buf(), size)
+    }
+
+    /// `buf` has to be empty, its allocator is used for all allocations.
+    fn with_buf(buf: Buf<T>, size: usize) -> Self {
         let mut data = Data {
-            data: vec![],
+            data: buf,
             uninitialized: 0,
+            scrub: false,
         };
 
         unsafe {
@@ -136,55 +175,158 @@ impl<T> Data<T> {
         self.data.get_unchecked(index)
     }
 
-    unsafe fn put(&mut self, cursor: usize, elem: T) {
+    /// Stores `elem` under `cursor` and returns the element it replaces.
+    ///
+    /// The old element is left to the caller, so it can be dropped once the
+    /// bookkeeping is done; that way, a panicking destructor can't leave the
+    /// buffer in an inconsistent state.
+    #[must_use]
+    unsafe fn put(&mut self, cursor: usize, elem: T) -> Option<T> {
+        let slot = self.data.get_unchecked_mut(cursor) as *mut T;
         if self.uninitialized > 0 {
             // There is no element stored under `cursor`
             // -> do not drop anything!
-            ptr::write(self.data.get_unchecked_mut(cursor) as *mut T, elem);
+            ptr::write(slot, elem);
             self.uninitialized -= 1;
+
+            None
         } else {
-            // We can safely drop this, it's initialized.
-            *self.data.get_unchecked_mut(cursor) = elem;
+            let old = ptr::read(slot);
+            if self.scrub {
+                ptr::write_bytes(slot, 0, 1);
+            }
+            ptr::write(slot, elem);
+
+            Some(old)
+        }
+    }
+
+    /// Zeroes all slots, which must not hold initialized elements.
+    unsafe fn scrub_all(&mut self) {
+        if self.scrub {
+            ptr::write_bytes(self.data.as_mut_ptr(), 0, self.data.len());
         }
     }
 
     /// `cursor` is the first position that gets moved to the back,
     /// free memory will be created between `cursor - 1` and `cursor`.
     unsafe fn grow(&mut self, cursor: usize, by: usize) {
-        assert!(by >= self.data.len());
-
         // Calculate how many elements we need to move
         let to_move = self.data.len() - cursor;
 
         // Reserve space and set the new length
-        self.data.reserve_exact(by);
+        if self.scrub && self.data.capacity() - self.data.len() < by {
+            // Reallocate by hand, `reserve_exact` would free the old
+            // allocation without clearing it.
+            let len = self.data.len();
+            let mut data = buf_like(&self.data, len + by);
+            ptr::copy_nonoverlapping(self.data.as_ptr(), data.as_mut_ptr(), len);
+            ptr::write_bytes(self.data.as_mut_ptr(), 0, len);
+            self.data.set_len(0);
+            data.set_len(len);
+            self.data = data;
+        } else {
+            self.data.reserve_exact(by);
+        }
         let new = self.data.len() + by;
         self.data.set_len(new);
 
         // Move the elements after the cursor to the end of the buffer.
-        // Since we grew the buffer at least by the old length,
-        // the elements are non-overlapping.
-        let src = self.data.as_ptr().offset(cursor as isize);
-        let dst = self.data.as_mut_ptr().offset((cursor + by) as isize);
-        ptr::copy_nonoverlapping(src, dst, to_move);
+        // Unless we grew the buffer at least by the number of moved elements,
+        // the old and new slots overlap.
+        // For zero-sized types, this (and the pointer arithmetic) is a no-op;
+        // only the element counts matter.
+        let src = self.data.as_ptr().add(cursor);
+        let dst = self.data.as_mut_ptr().add(cursor + by);
+        ptr::copy(src, dst, to_move);
+        if self.scrub {
+            ptr::write_bytes(self.data.as_mut_ptr().add(cursor), 0, to_move.min(by));
+        }
 
         self.uninitialized += by;
     }
 
     /// Called when dropping the ring buffer.
+    ///
+    /// Like `Vec`, this keeps dropping the remaining elements if a destructor
+    /// panics.
     unsafe fn clean(&mut self, cursor: usize) {
+        let len = self.data.len();
+        let start = CircularIndex::new(cursor, len) + self.uninitialized;
+        let count = len - self.uninitialized;
+        let first = count.min(len - start);
+        let base = self.data.as_mut_ptr();
+        let scrub = self.scrub;
+
+        // Emptied up front, so a panic can't cause an element to be dropped
+        // twice
+        self.data.set_len(0);
+        self.uninitialized = 0;
+
+        // Dropped in reverse order, even while unwinding
+        let _scrub = OnDrop(|| {
+            if scrub {
+                ptr::write_bytes(base, 0, len);
+            }
+        });
+        let _second = OnDrop(|| {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(base, count - first));
+        });
+        ptr::drop_in_place(ptr::slice_from_raw_parts_mut(base.add(start), first));
+    }
+
+    /// Moves all initialized elements out of the buffer, in the order they
+    /// are found starting at `cursor`.
+    unsafe fn drain(&mut self, cursor: usize) -> Vec<T> {
         let mut cursor = CircularIndex::new(cursor, self.data.len());
         let end = cursor - 1;
+        let mut skip = self.uninitialized;
+        let mut drained = Vec::with_capacity(self.num_initialized());
 
         while let Some(i) = cursor.step(end) {
-            if self.uninitialized > 0 {
-                self.uninitialized -= 1;
+            if skip > 0 {
+                skip -= 1;
             } else {
-                ptr::drop_in_place(self.data.get_unchecked_mut(i) as *mut T);
+                drained.push(ptr::read(self.data.get_unchecked(i)));
             }
         }
 
-        self.data.set_len(0);
+        self.uninitialized = self.data.len();
+        self.scrub_all();
+
+        drained
+    }
+
+    /// Moves the `n` oldest elements out, given `cursor` follows the newest
+    /// one. There must be at least `n` initialized elements.
+    unsafe fn take_oldest(&mut self, cursor: usize, n: usize) -> Vec<T> {
+        let len = self.data.len();
+        let oldest = CircularIndex::new(cursor, len) + self.uninitialized;
+        let mut taken = Vec::with_capacity(n);
+
+        for i in 0..n {
+            let slot = self
+                .data
+                .as_mut_ptr()
+                .add(CircularIndex::new(oldest, len) + i);
+            taken.push(ptr::read(slot));
+            if self.scrub {
+                ptr::write_bytes(slot, 0, 1);
+            }
+        }
+        self.uninitialized += n;
+
+        taken
+    }
+
+    /// Moves the slots so the one at `index` becomes the first one.
+    fn rotate(&mut self, index: usize) {
+        let len = self.data.len();
+        // Some of the slots are uninitialized
+        let slots = unsafe {
+            slice::from_raw_parts_mut(self.data.as_mut_ptr() as *mut MaybeUninit<T>, len)
+        };
+        slots.rotate_left(index);
     }
 
     fn num_initialized(&self) -> usize {
@@ -202,12 +344,43 @@ impl<T: Debug> Debug for Data<T> {
 }
 
 #[derive(Copy, Clone, Debug)]
-struct Reader {
-    generation: usize,
+pub struct Reader {
     last_index: usize,
+    /// Sequence number of the next event this reader will observe
+    seq: u64,
+    /// Like `seq`, but for the priority lane
+    priority_seq: u64,
+    /// The `seq` this reader was registered with
+    start_seq: u64,
+    /// Number of elements overwritten before this reader observed them,
+    /// since it last read
+    missed: u64,
+    /// Number of elements this reader was moved past without reading them,
+    /// since it was registered
+    lost: u64,
+    /// Label for diagnostics
+    name: Option<&'static str>,
+    /// Distinguishes readers which reuse the same slot
+    serial: u64,
+    /// Hash of the element `read_dedup` yielded last
+    dedup: Option<u64>,
 }
 
 impl Reader {
+    fn inactive() -> Self {
+        Reader {
+            last_index: !0,
+            seq: 0,
+            priority_seq: 0,
+            start_seq: 0,
+            missed: 0,
+            lost: 0,
+            name: None,
+            serial: 0,
+            dedup: None,
+        }
+    }
+
     fn set_inactive(&mut self) {
         self.last_index = !0;
     }
@@ -216,21 +389,22 @@ impl Reader {
         self.last_index != !0
     }
 
-    fn distance_from(&self, last: CircularIndex, current_gen: usize) -> usize {
+    /// `total_written` tells apart being caught up and being a full lap
+    /// behind, which both put the reader at `last`.
+    fn distance_from(&self, last: CircularIndex, total_written: u64) -> usize {
         let this = CircularIndex {
             index: self.last_index,
-            size: last.size,
+            ..last
         };
 
         match this - last.index {
-            0 if self.generation == current_gen => last.size,
+            0 if self.seq == total_written => last.size,
             x => x,
         }
     }
 
-    fn needs_shift(&self, last_index: usize, current_gen: usize) -> bool {
-        self.last_index > last_index
-            || (self.last_index == last_index && self.generation != current_gen)
+    fn needs_shift(&self, last_index: usize, total_written: u64) -> bool {
+        self.last_index > last_index || (self.last_index == last_index && self.seq != total_written)
     }
 }
 
@@ -248,6 +422,8 @@ impl Reader {
 /// growing.
 pub struct ReaderId<T: 'static> {
     id: usize,
+    serial: u64,
+    name: Option<&'static str>,
     marker: PhantomData<&'static [T]>,
     reference: Reference,
     // stupid way to make this `Sync`
@@ -258,6 +434,8 @@ impl<T: 'static> fmt::Debug for ReaderId<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ReaderId")
             .field("id", &self.id)
+            .field("serial", &self.serial)
+            .field("name", &self.name)
             .field("marker", &self.marker)
             .field("reference", &self.reference)
             .field("drop_notifier", &self.drop_notifier)
@@ -265,142 +443,451 @@ impl<T: 'static> fmt::Debug for ReaderId<T> {
     }
 }
 
+impl<T: 'static> ReaderId<T> {
+    /// Returns a token identifying this reader, which can be passed to
+    /// `EventChannel::is_active` after the `ReaderId` is gone.
+    pub fn token(&self) -> ReaderToken {
+        ReaderToken {
+            instance: self.reference.as_usize(),
+            id: self.id,
+            serial: self.serial,
+        }
+    }
+}
+
 impl<T: 'static> Drop for ReaderId<T> {
     fn drop(&mut self) {
         let _ = self.drop_notifier.get_mut().send(self.id);
     }
 }
 
+/// An opaque handle to a reader, which doesn't keep it registered.
+///
+/// Created by `ReaderId::token`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ReaderToken {
+    instance: usize,
+    id: usize,
+    serial: u64,
+}
+
+/// The state of a registered reader, see `EventChannel::readers_iter`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReaderInfo {
+    /// Identifies the reader, compare with `ReaderId::token`.
+    pub token: ReaderToken,
+    /// The name the reader was registered with, if any.
+    pub name: Option<&'static str>,
+    /// The number of events the next `read` of the reader would return.
+    pub unread: usize,
+    /// The sequence number of the next event the reader observes, i.e. the
+    /// number of events written before it.
+    pub seq: u64,
+}
+
+/// A closure observing the elements written to a `RingBuffer`.
+pub type Observer<T> = Box<dyn FnMut(&T) + Send>;
+
+/// Storage for the reader slots of a `RingBuffer`, including the ids of
+/// slots which are free for reuse.
+pub trait ReaderSlots: Default {
+    fn slots(&self) -> &[UnsafeCell<Reader>];
+
+    fn slots_mut(&mut self) -> &mut [UnsafeCell<Reader>];
+
+    /// Adds a slot for `reader`, returns `None` if there is no room left.
+    fn push(&mut self, reader: Reader) -> Option<usize>;
+
+    fn pop_free(&mut self) -> Option<usize>;
+
+    fn push_free(&mut self, id: usize);
+
+    /// Makes room for `additional` more slots, if the slots are allocated.
+    fn reserve(&mut self, _additional: usize) {}
+}
+
+/// Reader slots which grow as needed.
 #[derive(Default)]
-struct ReaderMeta {
-    /// Free ids
+pub struct VecSlots {
     free: Vec<usize>,
     readers: Vec<UnsafeCell<Reader>>,
 }
 
-impl ReaderMeta {
+impl ReaderSlots for VecSlots {
+    fn slots(&self) -> &[UnsafeCell<Reader>] {
+        &self.readers
+    }
+
+    fn slots_mut(&mut self) -> &mut [UnsafeCell<Reader>] {
+        &mut self.readers
+    }
+
+    fn push(&mut self, reader: Reader) -> Option<usize> {
+        self.readers.push(UnsafeCell::new(reader));
+
+        Some(self.readers.len() - 1)
+    }
+
+    fn pop_free(&mut self) -> Option<usize> {
+        self.free.pop()
+    }
+
+    fn push_free(&mut self, id: usize) {
+        self.free.push(id);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.readers.reserve_exact(additional);
+        // Every slot may be freed at once
+        self.free
+            .reserve_exact(self.readers.capacity() - self.free.len());
+    }
+}
+
+/// At most `N` reader slots, stored inline.
+pub struct FixedSlots<const N: usize> {
+    free: [usize; N],
+    num_free: usize,
+    readers: [UnsafeCell<Reader>; N],
+    len: usize,
+}
+
+impl<const N: usize> Default for FixedSlots<N> {
+    fn default() -> Self {
+        FixedSlots {
+            free: [0; N],
+            num_free: 0,
+            readers: std::array::from_fn(|_| UnsafeCell::new(Reader::inactive())),
+            len: 0,
+        }
+    }
+}
+
+impl<const N: usize> ReaderSlots for FixedSlots<N> {
+    fn slots(&self) -> &[UnsafeCell<Reader>] {
+        &self.readers[..self.len]
+    }
+
+    fn slots_mut(&mut self) -> &mut [UnsafeCell<Reader>] {
+        &mut self.readers[..self.len]
+    }
+
+    fn push(&mut self, reader: Reader) -> Option<usize> {
+        let id = self.len;
+        let slot = self.readers.get_mut(id)?;
+        slot.with_mut(|r| unsafe { *r = reader });
+        self.len += 1;
+
+        Some(id)
+    }
+
+    fn pop_free(&mut self) -> Option<usize> {
+        self.num_free = self.num_free.checked_sub(1)?;
+
+        Some(self.free[self.num_free])
+    }
+
+    fn push_free(&mut self, id: usize) {
+        // Every slot is freed at most once, so this never exceeds `N`
+        self.free[self.num_free] = id;
+        self.num_free += 1;
+    }
+}
+
+#[derive(Default)]
+struct ReaderMeta<S> {
+    slots: S,
+    /// Number of readers ever allocated
+    serial: u64,
+}
+
+impl<S: ReaderSlots> ReaderMeta<S> {
     fn new() -> Self {
         Default::default()
     }
 
-    fn reader<T>(&self, id: &mut ReaderId<T>) -> Option<&mut Reader> {
-        self.readers.get(id.id).map(|r| unsafe { &mut *r.get() })
+    /// Runs `f` on the reader belonging to `id`.
+    ///
+    /// This only needs `&self` because the exclusive borrow of `id` already
+    /// guarantees nobody else accesses that reader concurrently; distinct
+    /// `ReaderId`s always refer to distinct cells.
+    fn with_reader<T, R>(
+        &self,
+        id: &mut ReaderId<T>,
+        f: impl FnOnce(&mut Reader) -> R,
+    ) -> Option<R> {
+        self.slots
+            .slots()
+            .get(id.id)
+            .map(|r| r.with_mut(|r| f(unsafe { &mut *r })))
+    }
+
+    /// Runs `f` on the reader belonging to `id`, without modifying it.
+    ///
+    /// As long as `id` is borrowed, nobody can modify the reader.
+    fn with_reader_ref<T, R>(&self, id: &ReaderId<T>, f: impl FnOnce(&Reader) -> R) -> Option<R> {
+        self.slots
+            .slots()
+            .get(id.id)
+            .map(|r| r.with(|r| f(unsafe { &*r })))
+    }
+
+    /// Returns the reader belonging to `id`.
+    ///
+    /// The reference escapes the `with_mut` closure, so `loom` stops tracking
+    /// the access once it returns. That's sound: `&mut self` rules out any
+    /// concurrent `with_reader`/`with_reader_ref` (those need a shared borrow
+    /// of the channel) for as long as the returned reference lives, so there
+    /// is nothing for `loom` to race it against. The same holds for
+    /// `readers_exclusive`.
+    fn reader_exclusive(&mut self, id: usize) -> &mut Reader {
+        self.slots.slots_mut()[id].with_mut(|r| unsafe { &mut *r })
     }
 
-    fn reader_exclusive(&mut self, id: usize) -> &mut Reader {
-        unsafe { &mut *self.readers[id].get() }
+    /// Returns all readers, including inactive ones. See `reader_exclusive`
+    /// for why the references may escape `with_mut`.
+    fn readers_exclusive(&mut self) -> impl Iterator<Item = &mut Reader> {
+        self.slots
+            .slots_mut()
+            .iter_mut()
+            .map(|r| r.with_mut(|r| unsafe { &mut *r }))
     }
 
     fn has_reader(&mut self) -> bool {
-        self.readers
-            .iter()
-            .map(|r| unsafe { &mut *r.get() })
-            .any(|r| r.active())
+        self.readers_exclusive().any(|r| r.active())
+    }
+
+    fn num_active(&mut self) -> usize {
+        self.readers_exclusive().filter(|r| r.active()).count()
     }
 
-    fn alloc(&mut self, last_index: usize, generation: usize) -> usize {
-        match self.free.pop() {
+    /// Stores `reader` in a free slot, returns `None` if there is none.
+    fn alloc(&mut self, mut reader: Reader) -> Option<usize> {
+        reader.serial = self.serial;
+        let id = match self.slots.pop_free() {
             Some(id) => {
-                self.reader_exclusive(id).last_index = last_index;
-                self.reader_exclusive(id).generation = generation;
+                *self.reader_exclusive(id) = reader;
 
                 id
             }
-            None => {
-                let id = self.readers.len();
-                self.readers.push(UnsafeCell::new(Reader {
-                    generation,
-                    last_index,
-                }));
+            None => self.slots.push(reader)?,
+        };
+        self.serial += 1;
 
-                id
-            }
+        Some(id)
+    }
+
+    fn is_active(&mut self, id: usize, serial: u64) -> bool {
+        id < self.slots.slots().len() && {
+            let reader = self.reader_exclusive(id);
+
+            reader.active() && reader.serial == serial
         }
     }
 
     fn remove(&mut self, id: usize) {
         self.reader_exclusive(id).set_inactive();
-        self.free.push(id);
-    }
+        self.slots.push_free(id);
+    }
+
+    /// Returns the id of the active reader with the most unread elements.
+    // This needs to be mutable since `readers` might be borrowed in `with_reader`!
+    fn nearest(&mut self, last: CircularIndex, total_written: u64) -> Option<usize> {
+        let (mut nearest, mut min) = (None, usize::MAX);
+        for (id, reader) in self.readers_exclusive().enumerate() {
+            if reader.active() {
+                let distance = reader.distance_from(last, total_written);
+                if distance < min {
+                    nearest = Some(id);
+                    min = distance;
+                }
+            }
+        }
 
-    // This needs to be mutable since `readers` might be borrowed in `reader`!
-    fn nearest_index(&mut self, last: CircularIndex, current_gen: usize) -> Option<&Reader> {
-        self.readers
-            .iter()
-            .map(|reader| unsafe { &*reader.get() })
-            .filter(|reader| reader.active())
-            .min_by_key(|reader| reader.distance_from(last, current_gen))
+        nearest
     }
 
-    fn shift(&mut self, last_index: usize, current_gen: usize, grow_by: usize) {
-        for reader in &mut self.readers {
-            let reader = unsafe { &mut *reader.get() } as &mut Reader;
+    fn shift(&mut self, last_index: usize, total_written: u64, grow_by: usize) {
+        for reader in self.readers_exclusive() {
             if !reader.active() {
                 continue;
             }
 
-            if reader.needs_shift(last_index, current_gen) {
+            if reader.needs_shift(last_index, total_written) {
                 reader.last_index += grow_by;
             }
         }
     }
 }
 
-unsafe impl Send for ReaderMeta {}
-unsafe impl Sync for ReaderMeta {}
+unsafe impl<S> Send for ReaderMeta<S> {}
+unsafe impl<S> Sync for ReaderMeta<S> {}
 
-/// Ring buffer, holding data of type `T`.
-pub struct RingBuffer<T> {
+/// Ring buffer, holding data of type `T` and keeping its readers in `S`.
+pub struct RingBuffer<T, S = VecSlots> {
+    /// See `set_auto_shrink`
+    auto_shrink: Option<f32>,
     available: usize,
     last_index: CircularIndex,
     data: Data<T>,
+    /// Number of `drain_matching` calls which removed elements. Those
+    /// renumber the elements, so `Position`s from before are stale.
+    drains: u64,
+    /// Ids of dropped readers. This never holds more ids than there are
+    /// reader slots: every registration runs `maintain` first, and a slot is
+    /// only reused after its id was received.
     free_rx: NoSharedAccess<Receiver<usize>>,
     free_tx: NoSharedAccess<Sender<usize>>,
-    generation: Wrapping<usize>,
     instance_id: InstanceId,
-    meta: ReaderMeta,
+    meta: ReaderMeta<S>,
+    /// The capacity the buffer was created with, `auto_shrink` never goes
+    /// below it
+    min_capacity: usize,
+    /// Id of the reader returned by `ReaderMeta::nearest`, or `!0` if it needs
+    /// to be looked up again. Reset whenever that reader moves or is removed,
+    /// or another reader might have moved behind it.
+    nearest: AtomicUsize,
+    /// `total_written` when all readers were last seen caught up by
+    /// `auto_shrink`
+    caught_up_at: u64,
+    growth: GrowthPolicy,
+    /// Called with every element before it is stored
+    observer: Option<NoSharedAccess<Observer<T>>>,
+    /// Elements of the priority lane which some reader hasn't observed yet,
+    /// oldest first
+    priority: Vec<T>,
+    priority_written: u64,
+    /// Minimum capacity, so `read_since` finds at least that many of the
+    /// newest elements
+    retention: usize,
+    total_written: u64,
+    #[cfg(feature = "check_reentrancy")]
+    writing: WriteFlag,
+    /// When the stored elements were written, oldest first. The newest entry
+    /// belongs to the newest element; entries of elements which are gone
+    /// already may be left at the front until `trim_timestamps`.
+    #[cfg(feature = "timestamps")]
+    written_at: VecDeque<Instant>,
 }
 
-impl<T: 'static> RingBuffer<T> {
+impl<T: 'static, S: ReaderSlots> RingBuffer<T, S> {
     /// Create a new ring buffer with the given max size.
+    ///
+    /// Panics if `size` is zero.
     pub fn new(size: usize) -> Self {
-        assert!(size > 1);
+        Self::with_nonzero(NonZeroUsize::new(size).expect("ring buffer size must be non-zero"))
+    }
+
+    /// Create a new ring buffer with the given max size.
+    pub fn with_nonzero(size: NonZeroUsize) -> Self {
+        Self::with_data(Data::new(size.get()))
+    }
+
+    /// Create a new ring buffer with the given max size, allocated with
+    /// `alloc`.
+    #[cfg(feature = "allocator-api2")]
+    pub fn with_nonzero_in<A>(size: NonZeroUsize, alloc: A) -> Self
+    where
+        A: allocator_api2::alloc::Allocator + Send + Sync + 'static,
+    {
+        let buf = Buf::new_in(crate::util::SharedAlloc::new(alloc));
+
+        Self::with_data(Data::with_buf(buf, size.get()))
+    }
 
+    fn with_data(data: Data<T>) -> Self {
+        let size = data.data.len();
         let (free_tx, free_rx) = mpsc::channel();
         let free_tx = NoSharedAccess::new(free_tx);
         let free_rx = NoSharedAccess::new(free_rx);
 
         RingBuffer {
+            auto_shrink: None,
             available: size,
             last_index: CircularIndex::at_end(size),
-            data: Data::new(size),
+            data,
+            drains: 0,
             free_rx,
             free_tx,
-            generation: Wrapping(0),
             instance_id: InstanceId::new("`ReaderId` was not allocated by this `EventChannel`"),
             meta: ReaderMeta::new(),
+            min_capacity: size,
+            nearest: AtomicUsize::new(!0),
+            caught_up_at: 0,
+            growth: GrowthPolicy::Double,
+            observer: None,
+            priority: Vec::new(),
+            priority_written: 0,
+            retention: 0,
+            total_written: 0,
+            #[cfg(feature = "check_reentrancy")]
+            writing: WriteFlag::default(),
+            #[cfg(feature = "timestamps")]
+            written_at: VecDeque::new(),
         }
     }
 
+    /// Returns the number of elements the buffer has room for.
+    pub fn capacity(&self) -> usize {
+        self.last_index.size
+    }
+
+    /// Sets whether the bytes of dropped or moved out elements are zeroed.
+    pub fn set_scrub(&mut self, scrub: bool) {
+        self.data.scrub = scrub;
+    }
+
+    /// Sets a closure which is called with every element written from now
+    /// on, before it is stored.
+    pub fn set_observer(&mut self, observer: Observer<T>) {
+        self.observer = Some(NoSharedAccess::new(observer));
+    }
+
+    /// Returns the number of elements ever written, which is also the sequence
+    /// number of the next element.
+    pub fn total_written(&self) -> u64 {
+        self.total_written
+    }
+
     /// Iterates over all elements of `iter` and pushes them to the buffer.
     pub fn iter_write<I>(&mut self, iter: I)
     where
         I: IntoIterator<Item = T>,
         I::IntoIter: ExactSizeIterator,
     {
-        let iter = iter.into_iter();
-        let len = iter.len();
-        if len > 0 {
-            self.ensure_additional(len);
-            for element in iter {
-                unsafe {
-                    self.data.put(self.last_index + 1, element);
+        self.write(|this| {
+            let iter = iter.into_iter();
+            let len = iter.len();
+            if this.auto_shrink.is_some() {
+                this.auto_shrink(len);
+            }
+            if len > 0 {
+                this.ensure_additional(len);
+                #[cfg(feature = "timestamps")]
+                let now = Instant::now();
+                for element in iter {
+                    if let Some(observer) = &mut this.observer {
+                        (observer.get_mut())(&element);
+                    }
+                    let old = unsafe { this.data.put(this.last_index + 1, element) };
+                    // Kept up to date with every element, in case dropping `old`
+                    // or advancing `iter` panics. Without readers, `len` may
+                    // exceed the capacity.
+                    this.last_index += 1;
+                    this.available = this.available.saturating_sub(1);
+                    this.total_written += 1;
+                    #[cfg(feature = "timestamps")]
+                    this.written_at.push_back(now);
+                    drop(old);
                 }
-                self.last_index += 1;
+                #[cfg(feature = "timestamps")]
+                this.trim_timestamps();
+            } else {
+                // Callers may write empty batches as their only regular tick, so
+                // dropped readers are still cleaned up
+                this.maintain();
             }
-            self.available -= len;
-            self.generation += Wrapping(1);
-        }
+        })
     }
 
     /// Removes all elements from a `Vec` and pushes them to the ring buffer.
@@ -415,6 +902,65 @@ impl<T: 'static> RingBuffer<T> {
         self.meta.has_reader()
     }
 
+    /// Returns the number of elements the slowest reader hasn't read yet.
+    pub fn max_lag(&mut self) -> usize {
+        self.maintain();
+
+        match self.nearest_distance() {
+            None => 0,
+            Some(distance) => self.last_index.size - distance,
+        }
+    }
+
+    /// Returns `true` if the slowest reader is a full lap behind and the
+    /// growth policy doesn't let the buffer grow, so the next write
+    /// overwrites unread elements.
+    pub fn is_full(&mut self) -> bool {
+        let size = self.last_index.size;
+
+        self.max_lag() == size && self.growth.next_size(size, size + 1) <= size
+    }
+
+    /// Returns the number of elements which can be written without growing
+    /// the buffer, and caches it for `ensure_additional`.
+    pub fn available(&mut self) -> usize {
+        self.maintain();
+        self.available = self.nearest_distance().unwrap_or(self.last_index.size);
+
+        self.available
+    }
+
+    /// Returns the token of the active reader with the most unread elements.
+    pub fn slowest_reader(&mut self) -> Option<ReaderToken> {
+        self.maintain();
+
+        let id = self.meta.nearest(self.last_index, self.total_written)?;
+
+        Some(ReaderToken {
+            instance: self.instance_id.as_usize(),
+            id,
+            serial: self.meta.reader_exclusive(id).serial,
+        })
+    }
+
+    /// Returns the number of elements that can be written before the
+    /// nearest reader's unread elements would be overwritten, or `None` if
+    /// there is no reader.
+    fn nearest_distance(&mut self) -> Option<usize> {
+        let (last, written) = (self.last_index, self.total_written);
+        let id = match *self.nearest.get_mut() {
+            id if id != !0 => id,
+            _ => {
+                let id = self.meta.nearest(last, written)?;
+                *self.nearest.get_mut() = id;
+
+                id
+            }
+        };
+
+        Some(self.meta.reader_exclusive(id).distance_from(last, written))
+    }
+
     /// Ensures that `num` elements can be inserted.
     /// Does nothing if there's enough space, grows the buffer otherwise.
     #[inline(always)]
@@ -429,15 +975,13 @@ impl<T: 'static> RingBuffer<T> {
     #[inline(never)]
     fn ensure_additional_slow(&mut self, num: usize) {
         self.maintain();
-        let left: usize = match self.meta.nearest_index(self.last_index, self.generation.0) {
+        let left: usize = match self.nearest_distance() {
             None => {
                 self.available = self.last_index.size;
 
                 return;
             }
-            Some(reader) => {
-                let left = reader.distance_from(self.last_index, self.generation.0);
-
+            Some(left) => {
                 self.available = left;
 
                 if left >= num {
@@ -448,31 +992,255 @@ impl<T: 'static> RingBuffer<T> {
             }
         };
         let grow_by = num - left;
-        let min_target_size = self.last_index.size + grow_by;
+        // `overrun` forgets the nearest reader, so it is looked up before
+        #[cfg(feature = "tracing")]
+        let (old_capacity, lagging_reader) = {
+            let id = *self.nearest.get_mut();
+
+            (
+                self.last_index.size,
+                Label(id, self.meta.reader_exclusive(id).name),
+            )
+        };
+        let grow_by = self.grow(self.last_index.size + grow_by);
+        self.available = grow_by + left;
+        if self.available < num {
+            // The buffer is bounded
+            self.overrun(num);
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            old_capacity,
+            new_capacity = self.last_index.size,
+            %lagging_reader,
+            "shrev event buffer grew"
+        );
+    }
+
+    /// Grows the buffer to hold at least `capacity` elements.
+    pub fn grow_to(&mut self, capacity: usize) {
+        if capacity <= self.last_index.size {
+            return;
+        }
+
+        self.maintain();
+        let grow_by = self.grow(capacity);
+        self.available += grow_by;
+    }
 
-        // Make sure size' = 2^n * size
-        let mut size = 2 * self.last_index.size;
-        while size < min_target_size {
-            size *= 2;
+    /// Grows the buffer to hold at least `min_size` elements as far as the
+    /// growth policy allows, and returns by how much it grew.
+    fn grow(&mut self, min_size: usize) -> usize {
+        let size = self.growth.next_size(self.last_index.size, min_size);
+        if size <= self.last_index.size {
+            return 0;
         }
 
         // Calculate adjusted growth
         let grow_by = size - self.last_index.size;
 
-        // Insert the additional elements
+        // Insert the additional elements after the newest one. If that one
+        // is in the last slot, there is nothing to move.
         unsafe {
-            self.data.grow(self.last_index + 1, grow_by);
+            self.data.grow(self.last_index.index + 1, grow_by);
+        }
+        self.last_index = CircularIndex::new(self.last_index.index, size);
+
+        self.meta
+            .shift(self.last_index.index, self.total_written, grow_by);
+
+        grow_by
+    }
+
+    /// Moves every reader forward which would have unread elements
+    /// overwritten by writing `num` elements, counting those as missed.
+    fn overrun(&mut self, num: usize) {
+        let (last, written) = (self.last_index, self.total_written);
+        for reader in self.meta.readers_exclusive().filter(|r| r.active()) {
+            let distance = reader.distance_from(last, written);
+            if distance < num {
+                let lost = num - distance;
+                reader.last_index = CircularIndex::new(reader.last_index, last.size) + lost;
+                reader.seq += lost as u64;
+                reader.missed += lost as u64;
+                reader.lost += lost as u64;
+            }
+        }
+        *self.nearest.get_mut() = !0;
+        self.available = num;
+    }
+
+    /// Sets how the buffer grows.
+    pub fn set_growth_policy(&mut self, policy: GrowthPolicy) {
+        policy.validate();
+        self.growth = policy;
+    }
+
+    /// Shrinks the buffer to `size`, keeping the newest elements which still
+    /// fit. `size` needn't be a power of two, `auto_shrink` never goes below
+    /// the starting capacity, which may be any size. All readers have to be
+    /// caught up.
+    fn shrink(&mut self, size: usize) {
+        let mut elements = unsafe { self.data.drain(self.last_index + 1) };
+        let mut data = Data::with_buf(buf_like(&self.data.data, 0), size);
+        data.scrub = self.data.scrub;
+        let mut old = mem::replace(&mut self.data, data);
+        // All elements were moved out above
+        unsafe { old.data.set_len(0) };
+        self.last_index = CircularIndex::at_end(size);
+
+        let kept = elements.split_off(elements.len().saturating_sub(size));
+        for element in kept {
+            // The new slots are uninitialized, so nothing is replaced
+            let _ = unsafe { self.data.put(self.last_index + 1, element) };
+            self.last_index += 1;
+        }
+        self.catch_up_all();
+        *self.nearest.get_mut() = !0;
+        #[cfg(feature = "timestamps")]
+        self.trim_timestamps();
+        // Dropped last, so a panicking destructor can't leave the buffer
+        // inconsistent
+        drop(elements);
+    }
+
+    /// Keeps the capacity at `window` or more, growing the buffer if needed.
+    pub fn set_retention(&mut self, window: usize) {
+        self.retention = window;
+        self.grow_to(window);
+    }
+
+    /// Sets the ratio for `auto_shrink`, or disables it with `None`.
+    pub fn set_auto_shrink(&mut self, ratio: Option<f32>) {
+        self.auto_shrink = ratio;
+        self.caught_up_at = self.total_written;
+    }
+
+    /// If all readers are caught up, shrinks the buffer to the power of two
+    /// fitting the elements written since the readers were last seen caught
+    /// up, given that's less than the capacity times the ratio. It always
+    /// fits the `incoming` elements of the write about to happen, and never
+    /// goes below the initial capacity or the retention.
+    ///
+    /// Since reads only borrow the buffer immutably, this runs at the start
+    /// of every write instead. At most the elements written in between were
+    /// ever unread at once, so that's the capacity the readers need.
+    fn auto_shrink(&mut self, incoming: usize) {
+        let ratio = match self.auto_shrink {
+            Some(ratio) => ratio,
+            None => return,
+        };
+        self.maintain();
+        if !self.all_caught_up() {
+            return;
+        }
+
+        let live = self.total_written.saturating_sub(self.caught_up_at) as usize;
+        self.caught_up_at = self.total_written;
+        let size = self.last_index.size;
+        if (live as f32) < size as f32 * ratio {
+            let fit = live
+                .max(incoming)
+                .max(self.retention)
+                .max(1)
+                .next_power_of_two()
+                .max(self.min_capacity);
+            if fit < size {
+                self.shrink(fit);
+            }
         }
-        self.last_index.size = size;
+    }
+
+    fn all_caught_up(&mut self) -> bool {
+        let (last_index, written) = (self.last_index.index, self.total_written);
 
         self.meta
-            .shift(self.last_index.index, self.generation.0, grow_by);
-        self.available = grow_by + left
+            .readers_exclusive()
+            .filter(|r| r.active())
+            .all(|r| r.last_index == last_index && r.seq == written)
+    }
+
+    /// Runs `f` as a write, which `check_reentrancy` flags for its whole
+    /// duration. Only writes that run user code (destructors, the observer or
+    /// predicates) need this, so `compact` and `drain` aren't flagged.
+    fn write<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        #[cfg(feature = "check_reentrancy")]
+        {
+            use std::panic::{self, AssertUnwindSafe};
+
+            self.writing.enter();
+            let result = panic::catch_unwind(AssertUnwindSafe(|| f(&mut *self)));
+            self.writing.exit();
+            result.unwrap_or_else(|payload| panic::resume_unwind(payload))
+        }
+        #[cfg(not(feature = "check_reentrancy"))]
+        f(self)
+    }
+
+    /// Drops the write times of elements which are no longer stored.
+    #[cfg(feature = "timestamps")]
+    fn trim_timestamps(&mut self) {
+        let excess = self
+            .written_at
+            .len()
+            .saturating_sub(self.data.num_initialized());
+        self.written_at.drain(..excess);
     }
 
     fn maintain(&mut self) {
         while let Ok(id) = self.free_rx.get_mut().try_recv() {
-            self.meta.remove(id);
+            self.remove_reader(id);
+        }
+    }
+
+    fn remove_reader(&mut self, id: usize) {
+        if id == *self.nearest.get_mut() {
+            *self.nearest.get_mut() = !0;
+        }
+        self.meta.remove(id);
+    }
+
+    /// Writes `element` if that doesn't require growing the buffer, otherwise
+    /// returns it.
+    pub fn try_single_write(&mut self, element: T) -> Result<(), T> {
+        self.maintain();
+        match self.nearest_distance() {
+            Some(0) => Err(element),
+            _ => {
+                self.single_write(element);
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Like `try_single_write`, but waits up to `timeout` for readers blocking
+    /// the write to be dropped.
+    ///
+    /// Dropping readers is the only way to make room here: reading requires a
+    /// shared borrow of the buffer, which can't exist during this call.
+    pub fn try_single_write_timeout(&mut self, element: T, timeout: Duration) -> Result<(), T> {
+        // A deadline too far in the future to represent means waiting forever
+        let deadline = Instant::now().checked_add(timeout);
+        let mut element = element;
+        loop {
+            element = match self.try_single_write(element) {
+                Ok(()) => return Ok(()),
+                Err(element) => element,
+            };
+
+            let free_rx = self.free_rx.get_mut();
+            let freed = match deadline {
+                Some(deadline) => free_rx
+                    .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    .ok(),
+                None => free_rx.recv().ok(),
+            };
+            match freed {
+                Some(id) => self.remove_reader(id),
+                None => return Err(element),
+            }
         }
     }
 
@@ -483,75 +1251,919 @@ impl<T: 'static> RingBuffer<T> {
         self.iter_write(once(element));
     }
 
+    /// Writes an element to the priority lane, which `read` returns ahead of
+    /// all other elements.
+    ///
+    /// The lane only holds the elements some reader hasn't observed yet, and
+    /// at most `PRIORITY_CAPACITY` of them. Beyond that, the oldest element
+    /// is dropped and counts as lost for the readers which hadn't observed
+    /// it.
+    pub fn single_write_priority(&mut self, element: T) {
+        self.write(|this| {
+            this.maintain();
+            if let Some(observer) = &mut this.observer {
+                (observer.get_mut())(&element);
+            }
+
+            let oldest = this.priority_written - this.priority.len() as u64;
+            let min = this
+                .meta
+                .readers_exclusive()
+                .filter(|r| r.active())
+                .map(|r| r.priority_seq)
+                .min();
+            let evicted = match min {
+                Some(min) => {
+                    this.priority.drain(..(min - oldest) as usize);
+                    let evicted = match this.priority.len() {
+                        PRIORITY_CAPACITY => Some(this.priority.remove(0)),
+                        _ => None,
+                    };
+                    let oldest = this.priority_written - this.priority.len() as u64;
+                    for reader in this.meta.readers_exclusive().filter(|r| r.active()) {
+                        if reader.priority_seq < oldest {
+                            reader.lost += oldest - reader.priority_seq;
+                            reader.priority_seq = oldest;
+                        }
+                    }
+                    this.priority.push(element);
+
+                    evicted
+                }
+                // Nobody would observe it
+                None => {
+                    this.priority.clear();
+
+                    None
+                }
+            };
+            this.priority_written += 1;
+            // Dropped last, so a panicking destructor can't leave the lane
+            // inconsistent
+            drop(evicted);
+        })
+    }
+
+    /// Reserves slots for `additional` more readers than there are slots
+    /// yet, so registering them doesn't reallocate.
+    pub fn reserve_readers(&mut self, additional: usize) {
+        self.meta.slots.reserve(additional);
+    }
+
     /// Create a new reader id for this ring buffer.
     pub fn new_reader_id(&mut self) -> ReaderId<T> {
+        self.new_reader_id_back(0)
+    }
+
+    /// Create a new reader id for this ring buffer, which will observe the
+    /// last `n` elements still stored in the buffer.
+    ///
+    /// Panics if there is no free reader slot.
+    pub fn new_reader_id_back(&mut self, n: usize) -> ReaderId<T> {
+        self.try_new_reader_id_back(n)
+            .expect("no free reader slot left")
+    }
+
+    /// Like `new_reader_id_back`, but returns `None` if there is no free
+    /// reader slot.
+    pub fn try_new_reader_id_back(&mut self, n: usize) -> Option<ReaderId<T>> {
         self.maintain();
-        let last_index = self.last_index.index;
-        let generation = self.generation.0;
-        let id = self.meta.alloc(last_index, generation);
+        let n = n.min(self.data.num_initialized());
+        let reader_id = self.alloc_reader_id(Reader {
+            last_index: self.last_index - n,
+            seq: self.total_written - n as u64,
+            priority_seq: self.priority_written,
+            start_seq: self.total_written - n as u64,
+            missed: 0,
+            lost: 0,
+            name: None,
+            serial: 0,
+            dedup: None,
+        })?;
+        // The new reader blocks the `n` slots behind the head
+        self.available = self.available.min(self.last_index.size - n);
+        if n > 0 {
+            *self.nearest.get_mut() = !0;
+        }
+
+        Some(reader_id)
+    }
+
+    /// Like `new_reader_id`, but labels the reader with `name` for
+    /// diagnostics.
+    pub fn new_reader_id_named(&mut self, name: &'static str) -> ReaderId<T> {
+        let mut reader_id = self.new_reader_id();
+        self.meta.reader_exclusive(reader_id.id).name = Some(name);
+        reader_id.name = Some(name);
+
+        reader_id
+    }
+
+    /// Create a new reader id at the position of `reader_id`.
+    ///
+    /// Panics if there is no free reader slot.
+    pub fn clone_reader_id(&mut self, reader_id: &ReaderId<T>) -> ReaderId<T> {
+        self.maintain();
+        let reader = self.with_reader_ref(reader_id, |reader| *reader);
+
+        // `reader` already blocks the same slots, so `available` stays valid
+        self.alloc_reader_id(reader)
+            .expect("no free reader slot left")
+    }
+
+    fn alloc_reader_id(&mut self, reader: Reader) -> Option<ReaderId<T>> {
+        let id = self.meta.alloc(reader)?;
 
-        ReaderId {
+        let reader = self.meta.reader_exclusive(id);
+
+        Some(ReaderId {
             id,
+            serial: reader.serial,
+            name: reader.name,
             marker: PhantomData,
             reference: self.instance_id.reference(),
             drop_notifier: NoSharedAccess::new(self.free_tx.get_mut().clone()),
-        }
+        })
     }
 
-    /// Read data from the ring buffer, starting where the last read ended, and
-    /// up to where the last element was written.
-    pub fn read(&self, reader_id: &mut ReaderId<T>) -> StorageIterator<T> {
-        // Check if `reader_id` was actually created for this buffer.
-        // This is very important as `reader_id` is a token allowing memory access,
-        // and without this check a race could be caused by duplicate IDs.
-        self.instance_id.assert_eq(&reader_id.reference);
+    /// Returns the number of active readers.
+    pub fn reader_count(&mut self) -> usize {
+        self.maintain();
 
-        let (last_read_index, gen) = {
-            let reader = self.meta.reader(reader_id).unwrap_or_else(|| {
-                panic!(
-                    "ReaderId not registered: {}\n\
-                     This usually means that this ReaderId \
-                     was created by a different storage",
-                    reader_id.id
-                )
-            });
-            let old = reader.last_index;
-            reader.last_index = self.last_index.index;
-            let old_gen = reader.generation;
-            reader.generation = self.generation.0;
-
-            (old, old_gen)
-        };
-        let mut index = CircularIndex::new(last_read_index, self.last_index.size);
-        index += 1;
-        if gen == self.generation.0 {
-            // It is empty
-            index = CircularIndex::magic(index.size);
-        }
+        self.meta.num_active()
+    }
 
-        let iter = StorageIterator {
-            data: &self.data,
-            end: self.last_index.index,
-            index,
-        };
+    /// Returns `true` if the reader `token` belongs to is still registered.
+    pub fn is_active(&mut self, token: ReaderToken) -> bool {
+        self.maintain();
+
+        token.instance == self.instance_id.as_usize() && self.meta.is_active(token.id, token.serial)
+    }
+
+    /// Read data from the ring buffer, starting where the last read ended, and
+    /// up to where the last element was written. Pending elements of the
+    /// priority lane come first.
+    pub fn read(&self, reader_id: &mut ReaderId<T>) -> StorageIterator<'_, T> {
+        #[cfg(feature = "tracing")]
+        let label = Label(reader_id.id, reader_id.name);
+        let iter = self.with_reader(reader_id, |reader| {
+            let iter = self.pending_with_priority(reader);
+            reader.priority_seq = self.priority_written;
+            self.catch_up(reader);
+
+            iter
+        });
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(reader = %label, count = iter.len(), "shrev events read");
 
         iter
     }
-}
 
-impl<T: Debug> Debug for RingBuffer<T> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.debug_struct("RingBuffer")
-            .field("available", &self.available)
-            .field("instance_id", &self.instance_id)
+    /// Moves `reader_id` past all its unread elements, frees the slots of
+    /// dropped readers and shrinks the buffer if auto-shrinking allows it.
+    /// Returns the number of skipped elements and whether the buffer shrank.
+    pub fn relieve(&mut self, reader_id: &mut ReaderId<T>) -> (usize, bool) {
+        self.write(|this| {
+            let skipped = this.read(reader_id).len();
+            this.with_reader(reader_id, |reader| reader.lost += skipped as u64);
+            this.maintain();
+            let size = this.last_index.size;
+            if this.all_caught_up() {
+                // Nothing is unread anymore, so the skipped elements don't count
+                // towards the capacity the readers need
+                this.caught_up_at = this.total_written;
+                this.auto_shrink(0);
+            }
+
+            (skipped, this.last_index.size < size)
+        })
+    }
+
+    /// Like `read`, but leaves the priority lane alone.
+    pub fn read_normal(&self, reader_id: &mut ReaderId<T>) -> StorageIterator<'_, T> {
+        self.with_reader(reader_id, |reader| {
+            let iter = self.pending(reader);
+            self.catch_up(reader);
+
+            iter
+        })
+    }
+
+    /// Reads like `read_normal`, but returns pairs of consecutive elements.
+    ///
+    /// With `include_last_read`, the first pair starts with the element the
+    /// reader observed last, if it is still stored.
+    pub fn read_windows<'a>(
+        &'a self,
+        reader_id: &mut ReaderId<T>,
+        include_last_read: bool,
+    ) -> impl Iterator<Item = (&'a T, &'a T)> + 'a {
+        self.with_reader(reader_id, |reader| {
+            let last_read = match include_last_read {
+                true => self.last_read(reader),
+                false => None,
+            };
+            let iter = self.pending(reader);
+            self.catch_up(reader);
+
+            let skip = usize::from(last_read.is_none());
+            last_read
+                .into_iter()
+                .chain(iter.clone())
+                .zip(iter.skip(skip))
+        })
+    }
+
+    /// Reads like `read_normal`, but skips elements equal to the one before
+    /// them. The first element is compared to the one `read_dedup` yielded
+    /// last for this reader, by the hash stored on the reader.
+    pub fn read_dedup<'a>(&'a self, reader_id: &mut ReaderId<T>) -> impl Iterator<Item = &'a T> + 'a
+    where
+        T: PartialEq + Hash,
+    {
+        self.with_reader(reader_id, |reader| {
+            let iter = self.pending(reader);
+            let last_yielded = reader.dedup;
+            // The last element is either yielded or equal to the one yielded
+            // before it
+            if let Some(last) = iter.clone().next_back() {
+                reader.dedup = Some(hash_of(last));
+            }
+            self.catch_up(reader);
+
+            let mut prev: Option<&T> = None;
+            iter.filter(move |&elem| {
+                let changed = match prev {
+                    Some(prev) => prev != elem,
+                    None => last_yielded != Some(hash_of(elem)),
+                };
+                prev = Some(elem);
+
+                changed
+            })
+        })
+    }
+
+    /// Returns the element `reader` observed last, unless it was overwritten
+    /// or there is none.
+    fn last_read(&self, reader: &Reader) -> Option<&T> {
+        if reader.seq <= reader.start_seq {
+            return None;
+        }
+
+        self.events_between(reader.seq - 1, reader.seq)
+            .and_then(|mut iter| iter.next())
+    }
+
+    /// Read data up to and including the first element `stop` returns `true`
+    /// for. If there is no such element, this is equivalent to `read`.
+    pub fn read_until<F>(&self, reader_id: &mut ReaderId<T>, mut stop: F) -> StorageIterator<'_, T>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.with_reader(reader_id, |reader| {
+            let mut iter = self.pending(reader);
+            let len = iter.len();
+            let n = iter.clone().position(&mut stop).map_or(len, |i| i + 1);
+            self.advance(reader, n, len);
+
+            iter.split_front(n)
+        })
+    }
+
+    /// Reads like `read_normal`, but at most `max` elements; the reader is
+    /// only advanced past those.
+    pub fn read_with_limit(&self, reader_id: &mut ReaderId<T>, max: usize) -> LimitedRead<'_, T> {
+        self.with_reader(reader_id, |reader| {
+            let mut iter = self.pending(reader);
+            let len = iter.len();
+            let n = len.min(max);
+            self.advance(reader, n, len);
+
+            LimitedRead {
+                iter: iter.split_front(n),
+                remaining_after: len - n,
+            }
+        })
+    }
+
+    /// Returns the next element `read` would return for `reader_id`, and
+    /// advances it past only that one.
+    pub fn recv(&self, reader_id: &mut ReaderId<T>) -> Option<&T> {
+        self.with_reader(reader_id, |reader| {
+            if reader.priority_seq < self.priority_written {
+                let oldest = self.priority_written - self.priority.len() as u64;
+                let element = &self.priority[(reader.priority_seq - oldest) as usize];
+                reader.priority_seq += 1;
+
+                return Some(element);
+            }
+            if reader.seq == self.total_written {
+                return None;
+            }
+
+            let index = CircularIndex::new(reader.last_index, self.last_index.size) + 1;
+            let pending = (self.total_written - reader.seq) as usize;
+            self.advance(reader, 1, pending);
+
+            Some(unsafe { self.data.get(index) })
+        })
+    }
+
+    /// Like `read`, but first checks that the sequence number of the first
+    /// returned element is the one following the last element observed by
+    /// `reader_id`. On mismatch, the reader is left untouched.
+    pub fn read_checked(
+        &self,
+        reader_id: &mut ReaderId<T>,
+    ) -> Result<StorageIterator<'_, T>, GapError> {
+        self.with_reader(reader_id, |reader| {
+            let iter = self.pending(reader);
+            let got = self.total_written - iter.len() as u64;
+            // Overwritten elements were skipped by `seq` already
+            let expected = reader.seq - reader.missed;
+            if got != expected {
+                return Err(GapError { expected, got });
+            }
+            self.catch_up(reader);
+
+            Ok(iter)
+        })
+    }
+
+    /// Like `read`, but first checks that the state of the reader fits the
+    /// buffer. On failure, the reader is left untouched.
+    pub fn read_strict(
+        &self,
+        reader_id: &mut ReaderId<T>,
+    ) -> Result<StorageIterator<'_, T>, ConsistencyError> {
+        let violated = self.with_reader_ref(reader_id, |reader| {
+            let oldest_priority = self.priority_written - self.priority.len() as u64;
+            if reader.last_index >= self.last_index.size {
+                return Some("index out of bounds");
+            }
+            let pending = self.pending(reader).len();
+            if pending > self.data.num_initialized() {
+                return Some("unread elements are not initialized");
+            }
+            if reader.seq + pending as u64 != self.total_written {
+                return Some("sequence number doesn't match the index");
+            }
+            if reader.priority_seq < oldest_priority || reader.priority_seq > self.priority_written
+            {
+                return Some("priority lane position out of bounds");
+            }
+
+            None
+        });
+
+        match violated {
+            Some(violated) => Err(ConsistencyError { violated }),
+            None => Ok(self.read(reader_id)),
+        }
+    }
+
+    /// Like `read`, but tells apart having no new elements and having lost
+    /// elements.
+    pub fn poll(&self, reader_id: &mut ReaderId<T>) -> Poll<'_, T> {
+        self.with_reader(reader_id, |reader| {
+            let iter = self.pending(reader);
+            let got = self.total_written - iter.len() as u64;
+            let missed = reader.missed + got.saturating_sub(reader.seq);
+            self.catch_up(reader);
+
+            match missed {
+                0 if iter.len() == 0 => Poll::Empty,
+                0 => Poll::Ready(iter),
+                missed => Poll::Lagged { missed, iter },
+            }
+        })
+    }
+
+    /// Returns the elements `read` would return for `reader_id`, without
+    /// advancing it.
+    pub fn peek(&self, reader_id: &ReaderId<T>) -> StorageIterator<'_, T> {
+        self.with_reader_ref(reader_id, |reader| self.pending_with_priority(reader))
+    }
+
+    /// Returns the number of elements `read` would return for `reader_id`,
+    /// including those of the priority lane.
+    pub fn unread_count(&self, reader_id: &ReaderId<T>) -> usize {
+        self.with_reader_ref(reader_id, |reader| self.unread(reader))
+    }
+
+    /// Returns how long before `now` the next element outside of the
+    /// priority lane `read` would return for `reader_id` was written, or
+    /// `None` if there is none.
+    #[cfg(feature = "timestamps")]
+    pub fn oldest_unread_age(&self, reader_id: &ReaderId<T>, now: Instant) -> Option<Duration> {
+        let seq = self.with_reader_ref(reader_id, |reader| reader.seq);
+        // The newest entry belongs to the newest element
+        let behind = (self.total_written - seq) as usize;
+        let index = self.written_at.len().checked_sub(behind)?;
+
+        match behind {
+            0 => None,
+            _ => Some(now.saturating_duration_since(self.written_at[index])),
+        }
+    }
+
+    fn unread(&self, reader: &Reader) -> usize {
+        self.pending(reader).len() + (self.priority_written - reader.priority_seq) as usize
+    }
+
+    /// Returns the state of all active readers.
+    pub fn readers_info(&mut self) -> impl Iterator<Item = ReaderInfo> + '_ {
+        self.maintain();
+
+        let this = &*self;
+        let instance = this.instance_id.as_usize();
+        this.meta
+            .slots
+            .slots()
+            .iter()
+            .enumerate()
+            .filter_map(move |(id, reader)| {
+                // `&mut self` rules out concurrent reads
+                reader.with(|reader| {
+                    let reader = unsafe { &*reader };
+
+                    reader.active().then(|| ReaderInfo {
+                        token: ReaderToken {
+                            instance,
+                            id,
+                            serial: reader.serial,
+                        },
+                        name: reader.name,
+                        unread: this.unread(reader),
+                        seq: reader.seq,
+                    })
+                })
+            })
+    }
+
+    /// Panics if `reader_id` has unread elements.
+    #[track_caller]
+    pub fn assert_drained(&self, reader_id: &ReaderId<T>) {
+        let unread = self.unread_count(reader_id);
+        assert!(
+            unread == 0,
+            "reader {} still has {} unread events",
+            Label(reader_id.id, reader_id.name),
+            unread
+        );
+    }
+
+    /// Returns how many more elements `ahead` has read than `behind`, or 0
+    /// if it isn't ahead.
+    pub fn position_delta(&self, ahead: &ReaderId<T>, behind: &ReaderId<T>) -> usize {
+        let ahead = self.with_reader_ref(ahead, |reader| reader.seq);
+        let behind = self.with_reader_ref(behind, |reader| reader.seq);
+
+        ahead.saturating_sub(behind) as usize
+    }
+
+    /// Returns how many elements `reader_id` was moved past without reading
+    /// them since it was registered.
+    pub fn events_lost_for(&self, reader_id: &ReaderId<T>) -> u64 {
+        self.with_reader_ref(reader_id, |reader| reader.lost)
+    }
+
+    /// Returns the current position of `reader_id`.
+    pub fn position_of(&self, reader_id: &ReaderId<T>) -> Position {
+        self.with_reader_ref(reader_id, |reader| Position {
+            instance: self.instance_id.as_usize(),
+            drains: self.drains,
+            seq: reader.seq,
+        })
+    }
+
+    /// Returns the position of a reader which has observed every element
+    /// written so far.
+    pub fn position(&self) -> Position {
+        Position {
+            instance: self.instance_id.as_usize(),
+            drains: self.drains,
+            seq: self.total_written,
+        }
+    }
+
+    /// Returns the elements written after `pos` which are still stored,
+    /// and the position following them. If `drain_matching` removed elements
+    /// since `pos` was created, all stored elements are returned.
+    pub fn read_since(&self, pos: Position) -> (StorageIterator<'_, T>, Position) {
+        assert_eq!(
+            pos.instance,
+            self.instance_id.as_usize(),
+            "`Position` was not created by this `EventChannel`"
+        );
+        let oldest = self.total_written - self.data.num_initialized() as u64;
+        let start = match pos.drains == self.drains {
+            true => pos.seq.clamp(oldest, self.total_written),
+            false => oldest,
+        };
+        let iter = self
+            .events_between(start, self.total_written)
+            .expect("range is stored");
+
+        (iter, self.position())
+    }
+
+    /// Begins a read which only takes effect once committed with
+    /// `commit_read`.
+    pub fn begin_read(&self, reader_id: &ReaderId<T>) -> PendingRead<'_, T> {
+        self.with_reader_ref(reader_id, |reader| PendingRead {
+            iter: self.pending(reader),
+            id: reader_id.id,
+            seq: reader.seq,
+        })
+    }
+
+    /// Advances `reader_id` past the elements of `pending`, unless it has
+    /// been moved since `pending` was created.
+    pub fn commit_read(
+        &self,
+        reader_id: &mut ReaderId<T>,
+        pending: PendingRead<'_, T>,
+    ) -> Result<(), StaleReadError> {
+        assert!(
+            ptr::eq(pending.iter.data, &self.data) && pending.id == reader_id.id,
+            "`PendingRead` was not created for this `ReaderId`"
+        );
+
+        self.with_reader(reader_id, |reader| {
+            if reader.seq != pending.seq {
+                return Err(StaleReadError);
+            }
+            self.catch_up(reader);
+
+            Ok(())
+        })
+    }
+
+    /// Moves `to` to the position of `from`, so both observe the same
+    /// elements next.
+    pub fn copy_position(&mut self, from: &ReaderId<T>, to: &mut ReaderId<T>) {
+        let source = self.with_reader_ref(from, |reader| *reader);
+        self.with_reader(to, |reader| {
+            reader.last_index = source.last_index;
+            reader.seq = source.seq;
+            reader.start_seq = source.start_seq;
+        });
+        *self.nearest.get_mut() = !0;
+    }
+
+    /// Runs `f` on the reader state associated with `reader_id`.
+    fn with_reader<R>(&self, reader_id: &mut ReaderId<T>, f: impl FnOnce(&mut Reader) -> R) -> R {
+        self.check_reader(reader_id);
+        // Only store if needed, concurrent reads shouldn't contend on this
+        if self.nearest.load(AtomicOrdering::Relaxed) == reader_id.id {
+            self.nearest.store(!0, AtomicOrdering::Relaxed);
+        }
+
+        let id = reader_id.id;
+        self.meta
+            .with_reader(reader_id, f)
+            .unwrap_or_else(|| not_registered(id))
+    }
+
+    /// Runs `f` on the reader state associated with `reader_id`, without
+    /// modifying it.
+    fn with_reader_ref<R>(&self, reader_id: &ReaderId<T>, f: impl FnOnce(&Reader) -> R) -> R {
+        self.check_reader(reader_id);
+
+        self.meta
+            .with_reader_ref(reader_id, f)
+            .unwrap_or_else(|| not_registered(reader_id.id))
+    }
+
+    fn check_reader(&self, reader_id: &ReaderId<T>) {
+        // Check if `reader_id` was actually created for this buffer.
+        // This is very important as `reader_id` is a token allowing memory access,
+        // and without this check a race could be caused by duplicate IDs.
+        self.instance_id.assert_eq(&reader_id.reference);
+    }
+
+    /// Returns an iterator over the elements `reader` hasn't observed yet.
+    fn pending(&self, reader: &Reader) -> StorageIterator<'_, T> {
+        let mut index = CircularIndex::new(reader.last_index, self.last_index.size);
+        index += 1;
+        if reader.seq == self.total_written {
+            // It is empty
+            index = CircularIndex::magic(index.size);
+        }
+
+        StorageIterator {
+            priority: [].iter(),
+            data: &self.data,
+            end: self.last_index.index,
+            index,
+        }
+    }
+
+    /// Like `pending`, but starting with the elements of the priority lane
+    /// `reader` hasn't observed yet.
+    fn pending_with_priority(&self, reader: &Reader) -> StorageIterator<'_, T> {
+        let mut iter = self.pending(reader);
+        let oldest = self.priority_written - self.priority.len() as u64;
+        iter.priority = self.priority[(reader.priority_seq - oldest) as usize..].iter();
+
+        iter
+    }
+
+    /// Moves `reader` to the most recently written element.
+    fn catch_up(&self, reader: &mut Reader) {
+        reader.last_index = self.last_index.index;
+        reader.seq = self.total_written;
+        reader.missed = 0;
+    }
+
+    /// Moves `reader` past the next `n` of its `pending` elements.
+    fn advance(&self, reader: &mut Reader, n: usize, pending: usize) {
+        if n == pending {
+            self.catch_up(reader);
+        } else {
+            // `seq` stays behind `total_written`, since the reader is not caught up
+            reader.last_index = CircularIndex::new(reader.last_index, self.last_index.size) + n;
+            reader.seq += n as u64;
+            reader.missed = 0;
+        }
+    }
+
+    /// Moves all active readers to the most recently written element.
+    fn catch_up_all(&mut self) {
+        let (last_index, seq) = (self.last_index.index, self.total_written);
+        for reader in self.meta.readers_exclusive().filter(|r| r.active()) {
+            reader.last_index = last_index;
+            reader.seq = seq;
+        }
+        self.available = self.last_index.size;
+    }
+
+    /// Returns an iterator over all elements stored in the buffer, oldest
+    /// first, regardless of whether they have been read already.
+    pub fn iter_all(&self) -> StorageIterator<'_, T> {
+        let index = match self.data.num_initialized() {
+            0 => CircularIndex::magic(self.last_index.size),
+            len => CircularIndex::new(self.last_index - (len - 1), self.last_index.size),
+        };
+
+        StorageIterator {
+            priority: [].iter(),
+            data: &self.data,
+            end: self.last_index.index,
+            index,
+        }
+    }
+
+    /// Returns an iterator over the elements with sequence numbers in
+    /// `start..end`, or `None` unless all of them are still stored.
+    pub fn events_between(&self, start: u64, end: u64) -> Option<StorageIterator<'_, T>> {
+        let oldest = self.total_written - self.data.num_initialized() as u64;
+        if start > end || start < oldest || end > self.total_written {
+            return None;
+        }
+
+        // The newest element has the sequence number `total_written - 1`
+        let behind = |seq: u64| (self.total_written - 1 - seq) as usize;
+        let (index, end) = match start == end {
+            true => (
+                CircularIndex::magic(self.last_index.size),
+                self.last_index.index,
+            ),
+            false => (
+                CircularIndex::new(self.last_index - behind(start), self.last_index.size),
+                self.last_index - behind(end - 1),
+            ),
+        };
+
+        Some(StorageIterator {
+            priority: [].iter(),
+            data: &self.data,
+            end,
+            index,
+        })
+    }
+
+    /// Moves all elements out of the buffer, oldest first. All readers are
+    /// caught up afterwards.
+    pub fn drain(&mut self) -> Vec<T> {
+        self.maintain();
+        self.catch_up_all();
+        #[cfg(feature = "timestamps")]
+        self.written_at.clear();
+
+        unsafe { self.data.drain(self.last_index + 1) }
+    }
+
+    /// Drops all elements, including those of the priority lane, keeping the
+    /// element counts. All readers stay registered and are caught up
+    /// afterwards, the elements they hadn't observed count as lost.
+    pub fn clear(&mut self) {
+        self.write(|this| {
+            this.maintain();
+            let (written, priority_written) = (this.total_written, this.priority_written);
+            for reader in this.meta.readers_exclusive().filter(|r| r.active()) {
+                reader.lost += (written - reader.seq) + (priority_written - reader.priority_seq);
+                reader.priority_seq = priority_written;
+                reader.missed = 0;
+            }
+            let elements = this.drain();
+            let priority = mem::take(&mut this.priority);
+            this.caught_up_at = written;
+            *this.nearest.get_mut() = !0;
+            drop(elements);
+            drop(priority);
+        })
+    }
+
+    /// Drops all elements, including those of the priority lane, and resets
+    /// the element counts to zero, as well as the lost elements of the
+    /// readers. All readers stay registered and are caught up afterwards.
+    pub fn reset(&mut self) {
+        self.clear();
+        self.priority_written = 0;
+        self.total_written = 0;
+        self.caught_up_at = 0;
+        self.catch_up_all();
+        for reader in self.meta.readers_exclusive() {
+            reader.priority_seq = 0;
+            reader.start_seq = 0;
+            reader.lost = 0;
+        }
+        *self.nearest.get_mut() = !0;
+    }
+
+    /// Moves the stored elements to the start of the buffer, the oldest one
+    /// first, if all readers are caught up. Otherwise does nothing.
+    pub fn compact(&mut self) {
+        self.maintain();
+        if !self.all_caught_up() {
+            return;
+        }
+
+        let (num, size) = (self.data.num_initialized(), self.last_index.size);
+        // The oldest element follows the uninitialized slots
+        let oldest = CircularIndex::new(self.last_index + 1, size) + self.data.uninitialized;
+        self.data.rotate(oldest);
+        self.last_index.index = CircularIndex::new(num, size) - 1;
+        let last_index = self.last_index.index;
+        for reader in self.meta.readers_exclusive().filter(|r| r.active()) {
+            reader.last_index = last_index;
+        }
+    }
+
+    /// Drops all but the newest `n` elements. Readers which haven't observed
+    /// some of the dropped elements continue with the oldest one left,
+    /// counting the others as missed.
+    pub fn retain_last(&mut self, n: usize) {
+        self.write(|this| {
+            this.maintain();
+            let excess = this.data.num_initialized().saturating_sub(n);
+            if excess == 0 {
+                return;
+            }
+
+            let removed = unsafe { this.data.take_oldest(this.last_index + 1, excess) };
+            // The sequence number of the oldest element left
+            let oldest = this.total_written - n as u64;
+            let newest_removed = this.last_index - n;
+            for reader in this.meta.readers_exclusive().filter(|r| r.active()) {
+                if reader.seq < oldest {
+                    reader.missed += oldest - reader.seq;
+                    reader.lost += oldest - reader.seq;
+                    reader.last_index = newest_removed;
+                    reader.seq = oldest;
+                }
+            }
+            *this.nearest.get_mut() = !0;
+            #[cfg(feature = "timestamps")]
+            this.trim_timestamps();
+            // Dropped last, so a panicking destructor can't leave the buffer
+            // inconsistent
+            drop(removed);
+        })
+    }
+
+    /// Removes all elements `pred` returns `true` for and returns them, oldest
+    /// first.
+    ///
+    /// The remaining elements are moved together; every reader still
+    /// observes those it hadn't read yet. Their sequence numbers are
+    /// reassigned to stay contiguous, so the elements before a removed one
+    /// move up. Outstanding `Position`s become stale.
+    pub fn drain_matching<F>(&mut self, mut pred: F) -> Vec<T>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.write(|this| {
+            this.maintain();
+            // Evaluated up front, so a panicking `pred` leaves the buffer intact
+            let keep = this.iter_all().map(|e| !pred(e)).collect::<Vec<_>>();
+            if keep.iter().all(|&keep| keep) {
+                return Vec::new();
+            }
+            this.drains += 1;
+
+            // `kept_after[i]` is the number of kept elements in `keep[i..]`
+            let mut kept_after = vec![0; keep.len() + 1];
+            for (i, &keep) in keep.iter().enumerate().rev() {
+                kept_after[i] = kept_after[i + 1] + keep as usize;
+            }
+
+            #[cfg(feature = "timestamps")]
+            {
+                this.trim_timestamps();
+                let mut keep = keep.iter();
+                this.written_at.retain(|_| *keep.next().unwrap());
+            }
+
+            let last = this.last_index;
+            let elements = unsafe { this.data.drain(this.last_index + 1) };
+            let mut removed = Vec::with_capacity(keep.len() - kept_after[0]);
+            for (element, keep) in elements.into_iter().zip(keep) {
+                if keep {
+                    // The slots were drained, so nothing is replaced
+                    let _ = unsafe { this.data.put(this.last_index + 1, element) };
+                    this.last_index += 1;
+                } else {
+                    removed.push(element);
+                }
+            }
+
+            let num = kept_after.len() - 1;
+            let written = this.total_written;
+            // The new sequence number of the element which had `seq`, or would
+            // have had it
+            let renumber = |seq: u64| {
+                let behind = (written - seq).min(num as u64) as usize;
+
+                written - kept_after[num - behind] as u64
+            };
+            let mut max_unread = 0;
+            for reader in this.meta.readers_exclusive().filter(|r| r.active()) {
+                let unread = (last.size - reader.distance_from(last, written)).min(num);
+                let unread = kept_after[num - unread];
+                max_unread = max_unread.max(unread);
+
+                reader.last_index = this.last_index - unread;
+                reader.seq = written - unread as u64;
+                reader.start_seq = renumber(reader.start_seq);
+            }
+            this.available = this.last_index.size - max_unread;
+            *this.nearest.get_mut() = !0;
+
+            removed
+        })
+    }
+}
+
+/// Displays the name of a reader in quotes, or its id if it has none.
+struct Label(usize, Option<&'static str>);
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.1 {
+            Some(name) => write!(f, "'{}'", name),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+fn hash_of<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+
+    hasher.finish()
+}
+
+fn not_registered(id: usize) -> ! {
+    panic!(
+        "ReaderId not registered: {}\n\
+         This usually means that this ReaderId \
+         was created by a different storage",
+        id
+    )
+}
+
+impl<T: Debug, S> Debug for RingBuffer<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RingBuffer")
+            .field("available", &self.available)
+            .field("instance_id", &self.instance_id)
             .field("data", &self.data)
             .field("last_index", &self.last_index)
             .finish()
     }
 }
 
-impl<T> Drop for RingBuffer<T> {
+impl<T, S> Drop for RingBuffer<T, S> {
     fn drop(&mut self) {
+        // Loom reports ids of dropped readers still in the channel as leaked
+        #[cfg(feature = "loom")]
+        while self.free_rx.get_mut().try_recv().is_ok() {}
+
         unsafe {
             self.data.clean(self.last_index + 1);
         }
@@ -559,18 +2171,85 @@ impl<T> Drop for RingBuffer<T> {
 }
 
 /// Iterator over a slice of data in `RingBufferStorage`.
-#[derive(Debug)]
 pub struct StorageIterator<'a, T: 'a> {
+    /// Elements of the priority lane, returned before the others
+    priority: slice::Iter<'a, T>,
     data: &'a Data<T>,
     /// Inclusive end
     end: usize,
     index: CircularIndex,
 }
 
+/// Lists the remaining elements, without consuming the iterator.
+impl<'a, T: Debug> Debug for StorageIterator<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'a, T> StorageIterator<'a, T> {
+    /// Splits off the first `n` elements into a new iterator, `n` must not be
+    /// greater than `len()`.
+    fn split_front(&mut self, n: usize) -> StorageIterator<'a, T> {
+        let len = self.len();
+        debug_assert!(n <= len, "Bug in shrev");
+
+        let mut front = self.clone();
+        let priority = self.priority.as_slice();
+        if n <= priority.len() {
+            front.priority = priority[..n].iter();
+            front.index = CircularIndex::magic(self.index.size);
+            self.priority = priority[n..].iter();
+
+            return front;
+        }
+
+        self.priority = [].iter();
+        match n - priority.len() {
+            0 => front.index = CircularIndex::magic(self.index.size),
+            n => front.end = self.index + (n - 1),
+        }
+
+        if n == len {
+            self.index = CircularIndex::magic(self.index.size);
+        } else {
+            self.index += n - priority.len();
+        }
+
+        front
+    }
+
+    /// Pairs every element with the index of the slot it is stored in.
+    pub(crate) fn indexed(
+        self,
+    ) -> impl ExactSizeIterator<Item = (usize, &'a T)> + DoubleEndedIterator {
+        debug_assert!(self.priority.as_slice().is_empty(), "Bug in shrev");
+        let (first, size) = (self.index.index, self.index.size);
+
+        self.enumerate()
+            .map(move |(n, elem)| (CircularIndex::new(first, size) + n, elem))
+    }
+}
+
+impl<'a, T> Clone for StorageIterator<'a, T> {
+    fn clone(&self) -> Self {
+        StorageIterator {
+            priority: self.priority.clone(),
+            data: self.data,
+            end: self.end,
+            index: self.index,
+        }
+    }
+}
+
 impl<'a, T> Iterator for StorageIterator<'a, T> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<&'a T> {
+        if let Some(elem) = self.priority.next() {
+            return Some(elem);
+        }
+
         self.index
             .step(self.end)
             .map(|i| unsafe { self.data.get(i) })
@@ -582,6 +2261,43 @@ impl<'a, T> Iterator for StorageIterator<'a, T> {
 
         (len, Some(len))
     }
+
+    // The length is known, no need to walk the elements
+    fn count(self) -> usize {
+        self.len()
+    }
+
+    // Walks the (at most two) contiguous ranges directly, instead of
+    // checking for the end after every element like `next` does.
+    fn fold<B, F>(self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, &'a T) -> B,
+    {
+        let init = self.priority.fold(init, &mut f);
+        if self.index.is_magic() {
+            return init;
+        }
+
+        let (start, end, size) = (self.index.index, self.end, self.index.size);
+        let (first, second) = match start <= end {
+            true => (start..end + 1, 0..0),
+            false => (start..size, 0..end + 1),
+        };
+
+        let mut acc = init;
+        for i in first.chain(second) {
+            acc = f(acc, unsafe { self.data.get(i) });
+        }
+
+        acc
+    }
+
+    fn for_each<F>(self, mut f: F)
+    where
+        F: FnMut(&'a T),
+    {
+        self.fold((), |(), elem| f(elem));
+    }
 }
 
 impl<'a, T> DoubleEndedIterator for StorageIterator<'a, T> {
@@ -589,52 +2305,186 @@ impl<'a, T> DoubleEndedIterator for StorageIterator<'a, T> {
         self.index
             .step_back(&mut self.end)
             .map(|i| unsafe { self.data.get(i) })
+            .or_else(|| self.priority.next_back())
     }
 }
 
 impl<'a, T> ExactSizeIterator for StorageIterator<'a, T> {
     fn len(&self) -> usize {
-        match self.index.is_magic() {
+        let len = match self.index.is_magic() {
             true => 0,
             false => (CircularIndex::new(self.end, self.index.size) - self.index.index) + 1,
-        }
+        };
+
+        self.priority.len() + len
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// The position of a reader in the stream of events of a channel.
+///
+/// Positions of the same channel are ordered by the number of events
+/// consumed, so a reader which is further ahead has a greater position.
+/// Positions of different channels are neither equal nor ordered, and
+/// neither are positions from before and after `drain_matching` removed
+/// events.
+#[derive(Clone, Copy, Debug)]
+pub struct Position {
+    instance: usize,
+    drains: u64,
+    seq: u64,
+}
 
-    #[derive(Debug, Clone, PartialEq)]
-    struct Test {
-        pub id: u32,
+impl PartialEq for Position {
+    fn eq(&self, other: &Position) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
     }
+}
 
-    #[derive(Debug, Clone, PartialEq)]
-    struct Test2 {
-        pub id: u32,
+impl PartialOrd for Position {
+    fn partial_cmp(&self, other: &Position) -> Option<Ordering> {
+        if self.instance == other.instance && self.drains == other.drains {
+            Some(self.seq.cmp(&other.seq))
+        } else {
+            None
+        }
     }
+}
 
-    #[test]
-    fn test_size() {
-        let mut buffer = RingBuffer::<i32>::new(4);
+/// The outcome of a read with `poll`.
+#[derive(Debug)]
+pub enum Poll<'a, T: 'a> {
+    /// There were no new events.
+    Empty,
+    /// The events the reader hasn't observed yet.
+    Ready(StorageIterator<'a, T>),
+    /// Some events were lost before the reader could observe them; `iter`
+    /// contains the events after the gap.
+    Lagged {
+        /// The number of lost events.
+        missed: u64,
+        /// The events the reader hasn't observed yet.
+        iter: StorageIterator<'a, T>,
+    },
+}
 
-        buffer.single_write(55);
+/// The outcome of a read with `read_with_limit`.
+#[derive(Debug)]
+pub struct LimitedRead<'a, T: 'a> {
+    /// The events read, at most the limit.
+    pub iter: StorageIterator<'a, T>,
+    /// The number of events still unread after this read.
+    pub remaining_after: usize,
+}
 
-        let mut reader = buffer.new_reader_id();
+/// A read which has not advanced its reader yet.
+///
+/// Created by `begin_read`; the reader only moves past the events once the
+/// `PendingRead` is passed to `commit_read`.
+#[derive(Debug)]
+pub struct PendingRead<'a, T: 'a> {
+    iter: StorageIterator<'a, T>,
+    id: usize,
+    seq: u64,
+}
 
-        buffer.iter_write(0..16);
-        assert_eq!(buffer.read(&mut reader).len(), 16);
+impl<'a, T> PendingRead<'a, T> {
+    /// Returns an iterator over the events of this read.
+    pub fn events(&self) -> StorageIterator<'a, T> {
+        self.iter.clone()
+    }
 
-        buffer.iter_write(0..6);
-        assert_eq!(buffer.read(&mut reader).len(), 6);
+    /// Returns the number of events of this read.
+    pub fn len(&self) -> usize {
+        self.iter.len()
     }
 
-    #[test]
-    fn test_circular() {
-        let mut buffer = RingBuffer::<i32>::new(4);
+    /// Returns `true` if this read doesn't contain any events.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
 
-        buffer.single_write(55);
+impl<'a, T> Clone for PendingRead<'a, T> {
+    fn clone(&self) -> Self {
+        PendingRead {
+            iter: self.iter.clone(),
+            id: self.id,
+            seq: self.seq,
+        }
+    }
+}
+
+/// Iterator over fixed-size chunks of the events returned by a read.
+///
+/// Every chunk is a `StorageIterator` of its own; all of them have the
+/// requested length, except for the last one, which may be shorter.
+#[derive(Debug)]
+pub struct StorageChunks<'a, T: 'a> {
+    iter: StorageIterator<'a, T>,
+    chunk: usize,
+}
+
+impl<'a, T> StorageChunks<'a, T> {
+    pub(crate) fn new(iter: StorageIterator<'a, T>, chunk: usize) -> Self {
+        assert!(chunk != 0, "chunk size must be non-zero");
+
+        StorageChunks { iter, chunk }
+    }
+}
+
+impl<'a, T> Iterator for StorageChunks<'a, T> {
+    type Item = StorageIterator<'a, T>;
+
+    fn next(&mut self) -> Option<StorageIterator<'a, T>> {
+        match self.iter.len() {
+            0 => None,
+            len => Some(self.iter.split_front(len.min(self.chunk))),
+        }
+    }
+
+    // Needed to fulfill contract of `ExactSizeIterator`
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for StorageChunks<'a, T> {
+    fn len(&self) -> usize {
+        self.iter.len().div_ceil(self.chunk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Hash)]
+    struct Test {
+        pub id: u32,
+    }
+
+    #[test]
+    fn test_size() {
+        let mut buffer = RingBuffer::<i32>::new(4);
+
+        buffer.single_write(55);
+
+        let mut reader = buffer.new_reader_id();
+
+        buffer.iter_write(0..16);
+        assert_eq!(buffer.read(&mut reader).len(), 16);
+
+        buffer.iter_write(0..6);
+        assert_eq!(buffer.read(&mut reader).len(), 6);
+    }
+
+    #[test]
+    fn test_circular() {
+        let mut buffer = RingBuffer::<i32>::new(4);
+
+        buffer.single_write(55);
 
         let mut reader = buffer.new_reader_id();
 
@@ -661,258 +2511,1524 @@ mod tests {
         assert_eq!(buffer.data.num_initialized(), 15);
     }
 
-    #[test]
-    fn test_empty_read() {
-        let mut buffer = RingBuffer::<Test>::new(10);
+    #[test]
+    fn test_empty_read() {
+        let mut buffer = RingBuffer::<Test>::new(10);
+        let mut reader_id = buffer.new_reader_id();
+        let data = buffer.read(&mut reader_id);
+        assert_eq!(Vec::<Test>::default(), data.cloned().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_empty_read_rev() {
+        let mut buffer = RingBuffer::<Test>::new(10);
+        let mut reader_id = buffer.new_reader_id();
+        let data = buffer.read(&mut reader_id);
+        assert_eq!(
+            Vec::<Test>::default(),
+            data.rev().cloned().collect::<Vec<_>>()
+        )
+    }
+
+    #[test]
+    fn test_empty_read_write_before_id() {
+        let mut buffer = RingBuffer::<Test>::new(10);
+        buffer.drain_vec_write(&mut events(2));
+        let mut reader_id = buffer.new_reader_id();
+        let data = buffer.read(&mut reader_id);
+        assert_eq!(Vec::<Test>::default(), data.cloned().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_empty_read_write_before_id_rev() {
+        let mut buffer = RingBuffer::<Test>::new(10);
+        buffer.drain_vec_write(&mut events(2));
+        let mut reader_id = buffer.new_reader_id();
+        let data = buffer.read(&mut reader_id);
+        assert_eq!(
+            Vec::<Test>::default(),
+            data.rev().cloned().collect::<Vec<_>>()
+        )
+    }
+
+    #[test]
+    fn test_read() {
+        let mut buffer = RingBuffer::<Test>::new(10);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(2));
+        assert_eq!(
+            vec![Test { id: 0 }, Test { id: 1 }],
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            Vec::<Test>::new(),
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_read_rev() {
+        let mut buffer = RingBuffer::<Test>::new(10);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(2));
+        assert_eq!(
+            vec![Test { id: 1 }, Test { id: 0 }],
+            buffer
+                .read(&mut reader_id)
+                .rev()
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            Vec::<Test>::new(),
+            buffer
+                .read(&mut reader_id)
+                .rev()
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_read_wrap_around_rev() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(2));
+        // buffer should now be [--> 0, 1, -, -]
+        assert_eq!(
+            vec![Test { id: 1 }, Test { id: 0 }],
+            buffer
+                .read(&mut reader_id)
+                .rev()
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+        buffer.drain_vec_write(&mut events(3));
+        // buffer should now be [2, 1, --> 0, 1]
+        assert_eq!(
+            vec![Test { id: 2 }, Test { id: 1 }, Test { id: 0 }],
+            buffer
+                .read(&mut reader_id)
+                .rev()
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            Vec::<Test>::new(),
+            buffer
+                .read(&mut reader_id)
+                .rev()
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_read_wrap_around_overflow_rev() {
+        let mut buffer = RingBuffer::<Test>::new(5);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(2));
+        assert_eq!(
+            vec![Test { id: 1 }, Test { id: 0 }],
+            buffer
+                .read(&mut reader_id)
+                .rev()
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+        buffer.drain_vec_write(&mut events(6));
+        // buffer should now be [2, 1, --> 0, 1]
+        assert_eq!(
+            events(6).into_iter().rev().collect::<Vec<_>>(),
+            buffer
+                .read(&mut reader_id)
+                .rev()
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            Vec::<Test>::new(),
+            buffer
+                .read(&mut reader_id)
+                .rev()
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_write_overflow() {
+        let mut buffer = RingBuffer::<Test>::new(3);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(4));
+        let data = buffer.read(&mut reader_id);
+        assert_eq!(
+            vec![
+                Test { id: 0 },
+                Test { id: 1 },
+                Test { id: 2 },
+                Test { id: 3 },
+            ],
+            data.cloned().collect::<Vec<_>>()
+        );
+    }
+
+    /// If you're getting a compilation error here this test has failed!
+    #[test]
+    fn test_send_sync() {
+        trait SendSync: Send + Sync {
+            fn is_send_sync() -> bool;
+        }
+
+        impl<T> SendSync for T
+        where
+            T: Send + Sync,
+        {
+            fn is_send_sync() -> bool {
+                true
+            }
+        }
+
+        assert!(RingBuffer::<Test>::is_send_sync());
+        assert!(ReaderId::<Test>::is_send_sync());
+    }
+
+    #[test]
+    fn test_reader_reuse() {
+        let mut buffer = RingBuffer::<Test>::new(3);
+        {
+            let _reader_id = buffer.new_reader_id();
+        }
+        let _reader_id = buffer.new_reader_id();
+        assert_eq!(_reader_id.id, 0);
+        assert_eq!(buffer.meta.slots.readers.len(), 1);
+    }
+
+    #[test]
+    fn test_prevent_excess_growth() {
+        let mut buffer = RingBuffer::<Test>::new(3);
+        let mut reader_id = buffer.new_reader_id();
+        println!("Initial buffer state: {:#?}", buffer);
+        println!("--- first write ---");
+        buffer.drain_vec_write(&mut events(2));
+        println!("--- second write ---");
+        buffer.drain_vec_write(&mut events(2));
+        println!("--- writes complete ---");
+        // we wrote 0,1,0,1, if the buffer grew correctly we'll get all of these back.
+        assert_eq!(
+            vec![
+                Test { id: 0 },
+                Test { id: 1 },
+                Test { id: 0 },
+                Test { id: 1 },
+            ],
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+        );
+
+        buffer.drain_vec_write(&mut events(4));
+        // After writing 4 more events the buffer should have no reason to grow beyond 6
+        // (2 * 3).
+        assert_eq!(buffer.data.num_initialized(), 6);
+        assert_eq!(
+            vec![
+                Test { id: 0 },
+                Test { id: 1 },
+                Test { id: 2 },
+                Test { id: 3 },
+            ],
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_write_slice() {
+        let mut buffer = RingBuffer::<Test>::new(10);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.iter_write(events(2));
+        let data = buffer.read(&mut reader_id);
+        assert_eq!(
+            vec![Test { id: 0 }, Test { id: 1 }],
+            data.cloned().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn iter_write_empty() {
+        let mut buffer = RingBuffer::<Test>::new(10);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.iter_write(Vec::new());
+        let mut data = buffer.read(&mut reader_id);
+        assert_eq!(None, data.next());
+    }
+
+    #[test]
+    fn test_zero_sized() {
+        let mut buffer = RingBuffer::<()>::new(4);
+        let mut lagging = buffer.new_reader_id();
+        let mut reader = buffer.new_reader_id();
+
+        for n in 1..100 {
+            buffer.iter_write((0..n).map(|_| ()));
+            assert_eq!(buffer.read(&mut reader).len(), n);
+            assert_eq!(buffer.read(&mut reader).len(), 0);
+        }
+
+        // The lagging reader forced the buffer to grow
+        assert_eq!(buffer.read(&mut lagging).count(), (1..100).sum::<usize>());
+        assert_eq!(buffer.read(&mut lagging).rev().count(), 0);
+
+        // Wrap around a couple of times without growing
+        let capacity = buffer.capacity();
+        for _ in 0..10 {
+            buffer.iter_write((0..capacity - 1).map(|_| ()));
+            assert_eq!(buffer.read(&mut reader).len(), capacity - 1);
+            assert_eq!(buffer.read(&mut lagging).rev().count(), capacity - 1);
+        }
+        assert_eq!(buffer.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_zero_sized_drop() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+        struct Tick;
+
+        impl Drop for Tick {
+            fn drop(&mut self) {
+                DROPPED.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        {
+            let mut buffer = RingBuffer::<Tick>::new(4);
+            let mut reader = buffer.new_reader_id();
+            buffer.iter_write((0..10).map(|_| Tick));
+            assert_eq!(buffer.read(&mut reader).len(), 10);
+            buffer.iter_write((0..20).map(|_| Tick));
+            assert_eq!(buffer.read(&mut reader).len(), 20);
+        }
+
+        assert_eq!(DROPPED.load(Ordering::Relaxed), 30);
+    }
+
+    #[test]
+    fn test_panicking_drop() {
+        use std::{
+            panic::{AssertUnwindSafe, catch_unwind},
+            sync::atomic::{AtomicUsize, Ordering},
+        };
+
+        static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+        struct Bomb(u32);
+
+        impl Drop for Bomb {
+            fn drop(&mut self) {
+                DROPPED.fetch_add(1, Ordering::Relaxed);
+                if self.0 == 3 {
+                    panic!("Bomb 3 exploded");
+                }
+            }
+        }
+
+        let mut buffer = RingBuffer::<Bomb>::new(4);
+        let mut reader = buffer.new_reader_id();
+        buffer.iter_write((0..4).map(Bomb));
+        let _ = buffer.read(&mut reader);
+        // Wraps around, so the elements are split into 2, 3 and 4, 5
+        buffer.iter_write((4..6).map(Bomb));
+        assert_eq!(DROPPED.load(Ordering::Relaxed), 2);
+        drop(reader);
+
+        let result = catch_unwind(AssertUnwindSafe(|| drop(buffer)));
+        assert!(result.is_err());
+        assert_eq!(DROPPED.load(Ordering::Relaxed), 6);
+    }
+
+    #[test]
+    fn test_panicking_drop_on_overwrite() {
+        use std::{
+            panic::{AssertUnwindSafe, catch_unwind},
+            sync::atomic::{AtomicUsize, Ordering},
+        };
+
+        static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+        struct Bomb(u32);
+
+        impl Drop for Bomb {
+            fn drop(&mut self) {
+                DROPPED.fetch_add(1, Ordering::Relaxed);
+                if self.0 == 1 {
+                    panic!("Bomb 1 exploded");
+                }
+            }
+        }
+
+        let mut buffer = RingBuffer::<Bomb>::new(4);
+        let mut reader = buffer.new_reader_id();
+        buffer.iter_write((0..4).map(Bomb));
+        assert_eq!(buffer.read(&mut reader).len(), 4);
+
+        // Overwrites 0, 1 and 2, but dropping 1 panics
+        let result = catch_unwind(AssertUnwindSafe(|| buffer.iter_write((4..7).map(Bomb))));
+        assert!(result.is_err());
+        assert_eq!(DROPPED.load(Ordering::Relaxed), 2);
+        // Both events written before the panic are accounted for
+        assert_eq!(buffer.total_written(), 6);
+        assert_eq!(
+            buffer.read(&mut reader).map(|b| b.0).collect::<Vec<_>>(),
+            vec![4, 5]
+        );
+
+        buffer.iter_write((7..9).map(Bomb));
+        assert_eq!(
+            buffer.read(&mut reader).map(|b| b.0).collect::<Vec<_>>(),
+            vec![7, 8]
+        );
+        assert_eq!(DROPPED.load(Ordering::Relaxed), 4);
+        drop(reader);
+        drop(buffer);
+        // Bomb 6 was never created
+        assert_eq!(DROPPED.load(Ordering::Relaxed), 8);
+    }
+
+    #[test]
+    fn test_read_strict() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(6));
+        assert_eq!(buffer.read_strict(&mut reader_id).unwrap().len(), 6);
+
+        buffer.drain_vec_write(&mut events(2));
+        buffer.meta.reader_exclusive(reader_id.id).seq += 1;
+        assert_eq!(
+            buffer.read_strict(&mut reader_id).unwrap_err(),
+            ConsistencyError {
+                violated: "sequence number doesn't match the index"
+            }
+        );
+
+        buffer.meta.reader_exclusive(reader_id.id).seq -= 1;
+        buffer.meta.reader_exclusive(reader_id.id).last_index = buffer.capacity();
+        assert_eq!(
+            buffer.read_strict(&mut reader_id).unwrap_err().violated,
+            "index out of bounds"
+        );
+    }
+
+    #[test]
+    fn test_read_checked_gap() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(3));
+
+        // Pretend the reader lost the first event
+        buffer.meta.reader_exclusive(reader_id.id).seq = 1;
+        assert_eq!(
+            buffer.read_checked(&mut reader_id).unwrap_err(),
+            GapError {
+                expected: 1,
+                got: 0
+            }
+        );
+
+        // The reader was not advanced
+        assert_eq!(buffer.read(&mut reader_id).len(), 3);
+        assert_eq!(buffer.read_checked(&mut reader_id).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_poll() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        assert!(matches!(buffer.poll(&mut reader_id), Poll::Empty));
+
+        buffer.drain_vec_write(&mut events(2));
+        match buffer.poll(&mut reader_id) {
+            Poll::Ready(iter) => assert_eq!(iter.len(), 2),
+            poll => panic!("unexpected {:?}", poll),
+        }
+
+        // Pretend the reader lost two events
+        buffer.drain_vec_write(&mut events(1));
+        buffer.meta.reader_exclusive(reader_id.id).seq = 0;
+        match buffer.poll(&mut reader_id) {
+            Poll::Lagged { missed, iter } => {
+                assert_eq!(missed, 2);
+                assert_eq!(iter.cloned().collect::<Vec<_>>(), events(1));
+            }
+            poll => panic!("unexpected {:?}", poll),
+        }
+        assert!(matches!(buffer.poll(&mut reader_id), Poll::Empty));
+    }
+
+    #[test]
+    fn test_pending_drops_bounded() {
+        // Every dropped id is received before its slot can be reused, so the
+        // number of slots bounds the number of pending ids
+        let mut buffer = RingBuffer::<Test>::new(4);
+        for _ in 0..10_000 {
+            drop(buffer.new_reader_id());
+        }
+        assert_eq!(buffer.meta.slots.readers.len(), 1);
+
+        let readers = (0..100).map(|_| buffer.new_reader_id()).collect::<Vec<_>>();
+        drop(readers);
+        for _ in 0..10_000 {
+            drop(buffer.new_reader_id());
+        }
+        assert_eq!(buffer.meta.slots.readers.len(), 100);
+        buffer.maintain();
+        assert_eq!(buffer.meta.slots.free.len(), 100);
+    }
+
+    #[test]
+    fn test_fold() {
+        fn by_next<'a>(mut iter: StorageIterator<'a, Test>) -> Vec<&'a Test> {
+            // `from_fn` only ever calls `next`, never the overridden `fold`
+            std::iter::from_fn(|| iter.next()).collect()
+        }
+
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        for n in 0..6 {
+            buffer.drain_vec_write(&mut events(n));
+            let mut iter = buffer.read(&mut reader_id);
+            for _ in 0..3 {
+                let folded = iter.clone().fold(vec![], |mut elems, elem| {
+                    elems.push(elem);
+                    elems
+                });
+                assert_eq!(folded, by_next(iter.clone()));
+
+                let mut each = vec![];
+                iter.clone().for_each(|elem| each.push(elem));
+                assert_eq!(each, folded);
+
+                // Also compare partially consumed iterators
+                iter.next();
+                iter.next_back();
+            }
+        }
+    }
+
+    #[test]
+    fn test_priority_split() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(3));
+        buffer.single_write_priority(Test { id: 10 });
+        buffer.single_write_priority(Test { id: 11 });
+
+        let ids = |iter: StorageIterator<'_, Test>| iter.map(|t| t.id).collect::<Vec<_>>();
+        let mut iter = buffer.read(&mut reader_id);
+        assert_eq!(iter.len(), 5);
+        assert_eq!(ids(iter.split_front(1)), [10]);
+        assert_eq!(ids(iter.split_front(3)), [11, 0, 1]);
+        assert_eq!(ids(iter.clone()), [2]);
+        assert_eq!(ids(iter.split_front(0)), Vec::<u32>::new());
+        assert_eq!(ids(iter.split_front(1)), [2]);
+        assert_eq!(iter.len(), 0);
+
+        // Elements observed by every reader are released on the next write
+        buffer.single_write_priority(Test { id: 12 });
+        assert_eq!(buffer.priority.len(), 1);
+    }
+
+    #[test]
+    fn test_priority_capacity() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        let mut other = buffer.new_reader_id();
+        buffer.single_write_priority(Test { id: 0 });
+        assert_eq!(buffer.read(&mut other).len(), 1);
+
+        let cap = PRIORITY_CAPACITY as u32;
+        for id in 1..cap + 2 {
+            buffer.single_write_priority(Test { id });
+        }
+        assert_eq!(buffer.priority.len(), PRIORITY_CAPACITY);
+        assert_eq!(buffer.events_lost_for(&reader_id), 2);
+        assert_eq!(buffer.events_lost_for(&other), 1);
+        let ids = buffer
+            .read(&mut reader_id)
+            .map(|t| t.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, (2..cap + 2).collect::<Vec<_>>());
+        assert_eq!(buffer.unread_count(&reader_id), 0);
+
+        // Only the elements `other` hasn't read yet are kept
+        assert_eq!(buffer.read(&mut other).len(), PRIORITY_CAPACITY);
+        buffer.single_write_priority(Test { id: cap + 2 });
+        assert_eq!(buffer.priority.len(), 1);
+    }
+
+    #[test]
+    fn test_read_windows() {
+        let mut buffer = RingBuffer::<Test>::new(2);
+        let mut reader_id = buffer.new_reader_id();
+        let ids = |windows: &mut dyn Iterator<Item = (&Test, &Test)>| {
+            windows.map(|(a, b)| (a.id, b.id)).collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids(&mut buffer.read_windows(&mut reader_id, true)), []);
+        buffer.drain_vec_write(&mut events(1));
+        assert_eq!(ids(&mut buffer.read_windows(&mut reader_id, true)), []);
+        buffer.drain_vec_write(&mut events(3));
+        assert_eq!(
+            ids(&mut buffer.read_windows(&mut reader_id, true)),
+            [(0, 0), (0, 1), (1, 2)]
+        );
+        buffer.drain_vec_write(&mut events(2));
+        assert_eq!(
+            ids(&mut buffer.read_windows(&mut reader_id, false)),
+            [(0, 1)]
+        );
+
+        // The last read element was overwritten in the meantime
+        let mut other = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(1));
+        let _ = buffer.read(&mut other);
+        let _ = buffer.read(&mut reader_id);
+        let capacity = buffer.capacity();
+        buffer.drain_vec_write(&mut events(capacity as u32));
+        assert_eq!(buffer.capacity(), capacity);
+        assert_eq!(
+            ids(&mut buffer.read_windows(&mut other, true)),
+            [(0, 1), (1, 2), (2, 3)]
+        );
+
+        // Events written before the reader was registered were never read
+        buffer.drain_vec_write(&mut events(1));
+        let mut late = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(2));
+        assert_eq!(ids(&mut buffer.read_windows(&mut late, true)), [(0, 1)]);
+    }
+
+    #[test]
+    fn test_read_dedup() {
+        let mut buffer = RingBuffer::<Test>::new(8);
+        let mut reader_id = buffer.new_reader_id();
+        let write = |buffer: &mut RingBuffer<Test>, ids: &[u32]| {
+            buffer.iter_write(ids.iter().map(|&id| Test { id }));
+        };
+        let ids = |iter: &mut dyn Iterator<Item = &Test>| iter.map(|t| t.id).collect::<Vec<_>>();
+
+        write(&mut buffer, &[1, 1, 2, 2, 2, 1]);
+        assert_eq!(ids(&mut buffer.read_dedup(&mut reader_id)), [1, 2, 1]);
+
+        // Compared to the last element of the previous read
+        write(&mut buffer, &[1, 3, 3]);
+        assert_eq!(ids(&mut buffer.read_dedup(&mut reader_id)), [3]);
+        assert_eq!(
+            ids(&mut buffer.read_dedup(&mut reader_id)),
+            Vec::<u32>::new()
+        );
+
+        // Without a stored previous element, the first one is always returned
+        let mut other = buffer.new_reader_id();
+        write(&mut buffer, &[3, 3]);
+        assert_eq!(ids(&mut buffer.read_dedup(&mut other)), [3]);
+    }
+
+    #[test]
+    fn test_read_dedup_overwritten() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        buffer.set_growth_policy(GrowthPolicy::Bounded { max: 4 });
+        let mut reader_id = buffer.new_reader_id();
+        let ids = |iter: &mut dyn Iterator<Item = &Test>| iter.map(|t| t.id).collect::<Vec<_>>();
+
+        buffer.single_write(Test { id: 1 });
+        assert_eq!(ids(&mut buffer.read_dedup(&mut reader_id)), [1]);
+
+        // The event yielded last is overwritten, but still compared against
+        buffer.iter_write((0..6).map(|_| Test { id: 1 }));
+        assert_eq!(buffer.capacity(), 4);
+        assert_eq!(
+            ids(&mut buffer.read_dedup(&mut reader_id)),
+            Vec::<u32>::new()
+        );
+
+        buffer.iter_write([1, 2, 2, 2, 2, 2].iter().map(|&id| Test { id }));
+        assert_eq!(ids(&mut buffer.read_dedup(&mut reader_id)), [2]);
+    }
+
+    #[test]
+    fn test_grow_to() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(3));
+        let _ = buffer.read(&mut reader_id);
+        buffer.drain_vec_write(&mut events(3));
+
+        buffer.grow_to(4);
+        assert_eq!(buffer.capacity(), 4);
+        buffer.grow_to(9);
+        assert_eq!(buffer.capacity(), 16);
+
+        // The reader's position is preserved, and no further growth needed
+        buffer.drain_vec_write(&mut events(13));
+        assert_eq!(buffer.capacity(), 16);
+        assert_eq!(buffer.read(&mut reader_id).len(), 16);
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(6));
+        let _ = buffer.read(&mut reader_id);
+        buffer.drain_vec_write(&mut events(1));
+        let before = buffer.iter_all().cloned().collect::<Vec<_>>();
+
+        // The reader is behind
+        let first_index = |buffer: &RingBuffer<Test>, reader_id: &ReaderId<Test>| {
+            let pending = buffer.begin_read(reader_id);
+            pending.events().indexed().next().unwrap().0
+        };
+        let first = first_index(&buffer, &reader_id);
+        assert_ne!(first, 0);
+        buffer.compact();
+        assert_eq!(first_index(&buffer, &reader_id), first);
+        let _ = buffer.read(&mut reader_id);
+
+        buffer.compact();
+        assert_eq!(buffer.iter_all().cloned().collect::<Vec<_>>(), before);
+        assert_eq!(buffer.last_index.index, before.len() - 1);
+        buffer.drain_vec_write(&mut events(2));
+        let indices = buffer.read(&mut reader_id).indexed().map(|(i, _)| i);
+        assert_eq!(indices.collect::<Vec<_>>(), [before.len(), 0]);
+
+        // Without elements
+        let mut buffer = RingBuffer::<Test>::new(4);
+        buffer.drain_vec_write(&mut events(2));
+        let mut reader_id = buffer.new_reader_id();
+        let _ = buffer.drain();
+        buffer.compact();
+        buffer.drain_vec_write(&mut events(1));
+        assert_eq!(buffer.read(&mut reader_id).indexed().next().unwrap().0, 0);
+    }
+
+    #[test]
+    fn test_counter_past_u32() {
+        // A wrapping `usize` per write used to tell caught up readers apart;
+        // on 32-bit targets, it wrapped around at this point
+        let start = u64::from(u32::MAX) - 1;
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut idle = buffer.new_reader_id();
+        let mut reader_id = buffer.new_reader_id();
+        buffer.total_written = start;
+        for id in [idle.id, reader_id.id] {
+            let reader = buffer.meta.reader_exclusive(id);
+            reader.seq = start;
+            reader.start_seq = start;
+        }
+
+        for i in 0..4 {
+            buffer.single_write(Test { id: i });
+            assert_eq!(buffer.read(&mut reader_id).len(), 1);
+        }
+        // A full lap behind, not caught up
+        assert_eq!(buffer.max_lag(), 4);
+        assert_eq!(buffer.read(&mut idle).len(), 4);
+        assert_eq!(buffer.read(&mut idle).len(), 0);
+        assert_eq!(buffer.total_written(), start + 4);
+        assert_eq!(buffer.capacity(), 4);
+
+        // Sequence numbers keep counting past `u32::MAX`
+        let seq = u64::from(u32::MAX);
+        let events = buffer.events_between(seq, seq + 2).unwrap();
+        assert_eq!(events.map(|t| t.id).collect::<Vec<_>>(), vec![1, 2]);
+        assert!(buffer.events_between(start - 1, seq).is_none());
+    }
+
+    #[test]
+    fn test_auto_shrink() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        buffer.set_auto_shrink(Some(0.25));
+        let mut reader_id = buffer.new_reader_id();
+        let mut lagging = buffer.new_reader_id();
+
+        // A burst grows the buffer
+        buffer.drain_vec_write(&mut events(30));
+        assert_eq!(buffer.capacity(), 32);
+        assert_eq!(buffer.read(&mut reader_id).len(), 30);
+
+        // Never while a reader lags
+        buffer.drain_vec_write(&mut events(2));
+        assert_eq!(buffer.capacity(), 32);
+        assert_eq!(buffer.read(&mut lagging).len(), 32);
+        assert_eq!(buffer.read(&mut reader_id).len(), 2);
+
+        // 34 elements were written since the readers were caught up
+        buffer.drain_vec_write(&mut events(2));
+        assert_eq!(buffer.capacity(), 32);
+        assert_eq!(buffer.read(&mut lagging).len(), 2);
+        assert_eq!(buffer.read(&mut reader_id).len(), 2);
+
+        // Only 2 since then, but the write needs room for 3
+        buffer.drain_vec_write(&mut events(3));
+        assert_eq!(buffer.capacity(), 4);
+        // The newest of the elements already read were kept
+        assert_eq!(buffer.iter_all().count(), 4);
+        assert_eq!(buffer.read(&mut reader_id).len(), 3);
+        assert_eq!(buffer.read(&mut lagging).len(), 3);
+
+        buffer.single_write(Test { id: 7 });
+        assert_eq!(buffer.capacity(), 4);
+        let events = buffer.read(&mut reader_id).cloned().collect::<Vec<_>>();
+        assert_eq!(events, vec![Test { id: 7 }]);
+
+        // Never below the initial capacity, even if a single element fits
+        let mut buffer = RingBuffer::<Test>::new(4);
+        buffer.set_auto_shrink(Some(1.0));
+        let mut reader_id = buffer.new_reader_id();
+        for id in 0..2 {
+            buffer.single_write(Test { id });
+            assert_eq!(buffer.read(&mut reader_id).len(), 1);
+        }
+        assert_eq!(buffer.capacity(), 4);
+    }
+
+    #[test]
+    fn test_iter_debug() {
+        let mut buffer = RingBuffer::<Test>::new(2);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(3));
+        buffer.single_write_priority(Test { id: 9 });
+
+        let mut iter = buffer.read(&mut reader_id);
+        iter.next();
+        assert_eq!(
+            format!("{:?}", iter),
+            "[Test { id: 0 }, Test { id: 1 }, Test { id: 2 }]"
+        );
+        assert_eq!(iter.len(), 3);
+    }
+
+    #[test]
+    fn test_growth_policy() {
+        let sizes = |policy: GrowthPolicy| {
+            let mut buffer = RingBuffer::<Test>::new(4);
+            buffer.set_growth_policy(policy);
+            let mut reader_id = buffer.new_reader_id();
+            let mut sizes = Vec::new();
+            for i in 0..20 {
+                buffer.single_write(Test { id: i });
+                sizes.push(buffer.capacity());
+            }
+            // The elements were moved correctly
+            let read = buffer
+                .read(&mut reader_id)
+                .map(|t| t.id)
+                .collect::<Vec<_>>();
+            assert_eq!(read, (0..20).collect::<Vec<_>>());
+            sizes.dedup();
+
+            sizes
+        };
+
+        assert_eq!(sizes(GrowthPolicy::Double), vec![4, 8, 16, 32]);
+        assert_eq!(sizes(GrowthPolicy::Factor(1.5)), vec![4, 6, 9, 14, 21]);
+        assert_eq!(
+            sizes(GrowthPolicy::FixedIncrement(3)),
+            vec![4, 7, 10, 13, 16, 19, 22]
+        );
+    }
+
+    #[test]
+    fn test_small_increment_wrapped() {
+        let mut buffer = RingBuffer::<Test>::new(8);
+        buffer.set_scrub(true);
+        buffer.set_growth_policy(GrowthPolicy::FixedIncrement(2));
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(6));
+        assert_eq!(buffer.read(&mut reader_id).len(), 6);
+
+        // Wraps around, so growing moves 6 elements by 2 slots
+        buffer.drain_vec_write(&mut events(8));
+        buffer.single_write(Test { id: 8 });
+        assert_eq!(buffer.capacity(), 10);
+        let read = buffer
+            .read(&mut reader_id)
+            .map(|t| t.id)
+            .collect::<Vec<_>>();
+        assert_eq!(read, (0..9).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_bounded_growth() {
+        let mut buffer = RingBuffer::<Test>::new(2);
+        buffer.set_growth_policy(GrowthPolicy::Bounded { max: 4 });
+        let mut slow = buffer.new_reader_id();
+        let mut fast = buffer.new_reader_id();
+
+        buffer.drain_vec_write(&mut events(3));
+        assert_eq!(buffer.capacity(), 4);
+        assert_eq!(buffer.read(&mut fast).len(), 3);
+
+        // Only `slow` loses events
+        buffer.drain_vec_write(&mut events(2));
+        assert_eq!(buffer.capacity(), 4);
+        assert!(matches!(buffer.read_checked(&mut fast), Ok(iter) if iter.len() == 2));
+        assert_eq!(
+            buffer.read_checked(&mut slow).unwrap_err(),
+            GapError {
+                expected: 0,
+                got: 1
+            }
+        );
+        match buffer.poll(&mut slow) {
+            Poll::Lagged { missed, iter } => {
+                assert_eq!(missed, 1);
+                assert_eq!(iter.map(|t| t.id).collect::<Vec<_>>(), vec![1, 2, 0, 1]);
+            }
+            poll => panic!("unexpected {:?}", poll),
+        }
+
+        // More than fits at once
+        buffer.drain_vec_write(&mut events(6));
+        let iter = buffer.read_strict(&mut fast).unwrap();
+        assert_eq!(iter.map(|t| t.id).collect::<Vec<_>>(), vec![2, 3, 4, 5]);
+        assert!(matches!(
+            buffer.poll(&mut slow),
+            Poll::Lagged { missed: 2, .. }
+        ));
+        assert!(matches!(buffer.poll(&mut slow), Poll::Empty));
+        // Both lost the oldest elements of the write which didn't fit
+        assert_eq!(buffer.events_lost_for(&slow), 3);
+        assert_eq!(buffer.events_lost_for(&fast), 2);
+    }
+
+    #[test]
+    fn test_read_since() {
+        let mut buffer = RingBuffer::<Test>::new(2);
+        buffer.set_retention(5);
+        assert_eq!(buffer.capacity(), 8);
+        let start = buffer.position();
+
+        buffer.iter_write(events(3));
+        let (iter, pos) = buffer.read_since(start);
+        assert_eq!(iter.cloned().collect::<Vec<_>>(), events(3));
+        assert!(pos > start);
+        assert_eq!(buffer.read_since(pos).0.len(), 0);
+
+        // Without readers, the buffer doesn't grow, so only the newest
+        // elements of a stale position are left
+        buffer.iter_write(events(10));
+        let (iter, end) = buffer.read_since(pos);
+        assert_eq!(iter.cloned().collect::<Vec<_>>(), events(10)[2..]);
+        assert_eq!(end, buffer.position());
+
+        // Auto-shrinking keeps the retention window
+        buffer.set_auto_shrink(Some(1.0));
+        buffer.iter_write(events(1));
+        assert_eq!(buffer.capacity(), 8);
+    }
+
+    #[test]
+    fn test_relieve() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader = buffer.new_reader_id();
+        let mut paused = buffer.new_reader_id();
+        buffer.iter_write(events(30));
+        assert_eq!(buffer.read(&mut reader).len(), 30);
+        assert_eq!(buffer.capacity(), 32);
+
+        // Auto-shrinking is off
+        assert_eq!(buffer.relieve(&mut paused), (30, false));
+        assert_eq!(buffer.capacity(), 32);
+
+        buffer.set_auto_shrink(Some(0.5));
+        buffer.iter_write(events(30));
+        // The other reader still has unread elements
+        assert_eq!(buffer.relieve(&mut paused), (30, false));
+        buffer.read(&mut reader);
+        buffer.iter_write(events(3));
+        assert_eq!(buffer.read(&mut reader).len(), 3);
+        assert_eq!(buffer.relieve(&mut paused), (3, true));
+        assert_eq!(buffer.capacity(), 4);
+        assert_eq!(buffer.relieve(&mut paused), (0, false));
+        assert_eq!(buffer.events_lost_for(&paused), 63);
+        assert_eq!(buffer.events_lost_for(&reader), 0);
+    }
+
+    #[test]
+    fn test_count() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader = buffer.new_reader_id();
+        buffer.iter_write(events(3));
+        buffer.single_write_priority(Test { id: 9 });
+        buffer.iter_write(events(3));
+
+        let mut iter = buffer.read(&mut reader);
+        assert_eq!(iter.clone().count(), iter.len());
+        assert_eq!(iter.len(), 7);
+        iter.next();
+        iter.next_back();
+        assert_eq!(iter.clone().count(), 5);
+
+        // Through `by_ref`, the iterator is exhausted afterwards
+        assert_eq!(iter.by_ref().count(), 5);
+        assert_eq!(iter.len(), 0);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_large_write_growth() {
+        let mut buffer = RingBuffer::<u32>::new(4);
+        let mut reader = buffer.new_reader_id();
+        buffer.iter_write(0..1_000_000);
+
+        // Straight to the next power of two, without reserving more
+        assert_eq!(buffer.capacity(), 1 << 20);
+        assert_eq!(buffer.data.data.capacity(), 1 << 20);
+        assert!(buffer.read(&mut reader).copied().eq(0..1_000_000));
+    }
+
+    #[test]
+    fn test_retain_last() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut behind = buffer.new_reader_id();
+        let mut ahead = buffer.new_reader_id();
+        buffer.iter_write(events(6));
+        buffer.read_with_limit(&mut ahead, 5);
+
+        buffer.retain_last(3);
+        assert_eq!(
+            buffer.iter_all().cloned().collect::<Vec<_>>(),
+            events(6)[3..]
+        );
+        match buffer.poll(&mut behind) {
+            Poll::Lagged { iter, missed } => {
+                assert_eq!(iter.cloned().collect::<Vec<_>>(), events(6)[3..]);
+                assert_eq!(missed, 3);
+            }
+            _ => panic!("expected lost elements"),
+        }
+        assert_eq!(
+            buffer.read(&mut ahead).cloned().collect::<Vec<_>>(),
+            events(6)[5..]
+        );
+        assert_eq!(buffer.events_lost_for(&behind), 3);
+        assert_eq!(buffer.events_lost_for(&ahead), 0);
+
+        // Keeps working after wrapping around
+        buffer.iter_write(events(5));
+        buffer.retain_last(0);
+        assert_eq!(buffer.iter_all().len(), 0);
+        assert_eq!(buffer.read(&mut ahead).len(), 0);
+        buffer.iter_write(events(10));
+        buffer.retain_last(20);
+        assert_eq!(
+            buffer.read(&mut behind).cloned().collect::<Vec<_>>(),
+            events(10)
+        );
+    }
+
+    #[test]
+    fn test_reserve_readers() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        buffer.reserve_readers(8);
+        let (readers, free) = (&buffer.meta.slots.readers, &buffer.meta.slots.free);
+        assert!(readers.capacity() >= 8 && free.capacity() >= 8);
+        let (readers, free) = (readers.as_ptr(), free.as_ptr());
+
+        let ids = (0..8).map(|_| buffer.new_reader_id()).collect::<Vec<_>>();
+        drop(ids);
+        buffer.maintain();
+        assert_eq!(buffer.meta.slots.readers.as_ptr(), readers);
+        assert_eq!(buffer.meta.slots.free.as_ptr(), free);
+        assert_eq!(buffer.meta.slots.free.len(), 8);
+    }
+
+    #[test]
+    fn test_recv() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader = buffer.new_reader_id();
+        let mut other = buffer.new_reader_id();
+        assert_eq!(buffer.recv(&mut reader), None);
+
+        buffer.iter_write(events(3));
+        buffer.single_write_priority(Test { id: 10 });
+        let mut received = Vec::new();
+        while let Some(element) = buffer.recv(&mut reader) {
+            received.push(element.clone());
+        }
+        let read = buffer.read(&mut other).cloned().collect::<Vec<_>>();
+        assert_eq!(received, read);
+        buffer.read_strict(&mut reader).unwrap();
+
+        // Wrap around, with another reader holding the buffer in place
+        for round in 0..5 {
+            buffer.iter_write(events(3));
+            assert_eq!(buffer.recv(&mut reader), Some(&Test { id: 0 }));
+            assert_eq!(buffer.unread_count(&reader), 2);
+            if round % 2 == 0 {
+                assert_eq!(buffer.read(&mut reader).len(), 2);
+            } else {
+                assert_eq!(buffer.recv(&mut reader), Some(&Test { id: 1 }));
+                assert_eq!(buffer.recv(&mut reader), Some(&Test { id: 2 }));
+                assert_eq!(buffer.recv(&mut reader), None);
+            }
+            assert_eq!(buffer.read(&mut other).len(), 3);
+        }
+        assert_eq!(buffer.capacity(), 4);
+        buffer.read_strict(&mut reader).unwrap();
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing_overrun() {
+        use tracing::{
+            Event, Metadata,
+            field::{Field, Visit},
+            span,
+        };
+
+        /// Formats the fields of every event, like a real subscriber would
+        struct FormatAll;
+
+        impl Visit for FormatAll {
+            fn record_debug(&mut self, _: &Field, value: &dyn Debug) {
+                let _ = format!("{:?}", value);
+            }
+        }
+
+        impl tracing::Subscriber for FormatAll {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+                span::Id::from_u64(1)
+            }
+
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                event.record(&mut FormatAll);
+            }
+
+            fn enter(&self, _: &span::Id) {}
+
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let mut buffer = RingBuffer::<Test>::new(2);
+        buffer.set_growth_policy(GrowthPolicy::Bounded { max: 2 });
         let mut reader_id = buffer.new_reader_id();
-        let data = buffer.read(&mut reader_id);
-        assert_eq!(Vec::<Test>::default(), data.cloned().collect::<Vec<_>>())
+        tracing::subscriber::with_default(FormatAll, || {
+            buffer.iter_write(events(3));
+            assert_eq!(buffer.read(&mut reader_id).len(), 2);
+        });
     }
 
+    #[cfg(feature = "timestamps")]
     #[test]
-    fn test_empty_read_rev() {
-        let mut buffer = RingBuffer::<Test>::new(10);
+    fn test_oldest_unread_age() {
+        let mut buffer = RingBuffer::<Test>::new(4);
         let mut reader_id = buffer.new_reader_id();
-        let data = buffer.read(&mut reader_id);
-        assert_eq!(
-            Vec::<Test>::default(),
-            data.rev().cloned().collect::<Vec<_>>()
-        )
-    }
+        let mut lagging = buffer.new_reader_id();
+        buffer.iter_write((0..2).map(|id| Test { id }));
+        std::thread::sleep(Duration::from_millis(10));
+        buffer.iter_write((2..4).map(|id| Test { id }));
+
+        let now = Instant::now();
+        assert_eq!(buffer.read_with_limit(&mut reader_id, 2).iter.len(), 2);
+        let age = buffer.oldest_unread_age(&reader_id, now).unwrap();
+        let lagging_age = buffer.oldest_unread_age(&lagging, now).unwrap();
+        assert!(lagging_age >= age + Duration::from_millis(10));
+
+        // Follows the elements when they are removed
+        buffer.drain_matching(|t| t.id == 0);
+        assert_eq!(buffer.oldest_unread_age(&lagging, now), Some(lagging_age));
+        buffer.retain_last(2);
+        assert_eq!(buffer.oldest_unread_age(&lagging, now), Some(age));
+        assert_eq!(buffer.written_at.len(), 2);
+
+        // Only the write times of stored elements are kept
+        for id in 0..10 {
+            buffer.read(&mut reader_id);
+            buffer.read(&mut lagging);
+            buffer.single_write(Test { id });
+        }
+        assert_eq!(buffer.capacity(), 4);
+        assert_eq!(buffer.written_at.len(), 4);
+        assert!(buffer.oldest_unread_age(&lagging, Instant::now()).is_some());
 
-    #[test]
-    fn test_empty_read_write_before_id() {
-        let mut buffer = RingBuffer::<Test>::new(10);
-        buffer.drain_vec_write(&mut events(2));
-        let mut reader_id = buffer.new_reader_id();
-        let data = buffer.read(&mut reader_id);
-        assert_eq!(Vec::<Test>::default(), data.cloned().collect::<Vec<_>>())
+        buffer.clear();
+        assert_eq!(buffer.oldest_unread_age(&lagging, Instant::now()), None);
+        assert!(buffer.written_at.is_empty());
     }
 
     #[test]
-    fn test_empty_read_write_before_id_rev() {
-        let mut buffer = RingBuffer::<Test>::new(10);
-        buffer.drain_vec_write(&mut events(2));
+    fn test_empty_write_maintains() {
+        let mut buffer = RingBuffer::<Test>::new(4);
         let mut reader_id = buffer.new_reader_id();
-        let data = buffer.read(&mut reader_id);
-        assert_eq!(
-            Vec::<Test>::default(),
-            data.rev().cloned().collect::<Vec<_>>()
-        )
+        let dropped = buffer.new_reader_id();
+        buffer.single_write(Test { id: 0 });
+        let last_index = buffer.last_index.index;
+
+        drop(dropped);
+        buffer.iter_write(None);
+        assert_eq!(buffer.meta.num_active(), 1);
+        assert_eq!(buffer.last_index.index, last_index);
+        assert_eq!(buffer.total_written, 1);
+        assert_eq!(buffer.read(&mut reader_id).len(), 1);
     }
 
     #[test]
-    fn test_read() {
-        let mut buffer = RingBuffer::<Test>::new(10);
+    fn test_size_one() {
+        let mut buffer = RingBuffer::<Test>::new(1);
+        buffer.single_write(Test { id: 9 });
         let mut reader_id = buffer.new_reader_id();
-        buffer.drain_vec_write(&mut events(2));
+        let mut lagging = buffer.new_reader_id();
+        assert_eq!(buffer.read(&mut reader_id).len(), 0);
+
+        for i in 0..5 {
+            buffer.single_write(Test { id: i });
+            assert_eq!(
+                vec![Test { id: i }],
+                buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+            );
+        }
+        buffer.drain_vec_write(&mut events(3));
         assert_eq!(
-            vec![Test { id: 0 }, Test { id: 1 }],
+            events(3),
             buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
         );
 
+        let mut expected = events(5);
+        expected.extend(events(3));
         assert_eq!(
-            Vec::<Test>::new(),
-            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+            expected,
+            buffer.read(&mut lagging).cloned().collect::<Vec<_>>()
         );
     }
 
     #[test]
-    fn test_read_rev() {
-        let mut buffer = RingBuffer::<Test>::new(10);
-        let mut reader_id = buffer.new_reader_id();
-        buffer.drain_vec_write(&mut events(2));
+    fn test_reader_back() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        buffer.drain_vec_write(&mut events(3));
+
+        let mut none = buffer.new_reader_id_back(0);
+        let mut two = buffer.new_reader_id_back(2);
+        let mut all = buffer.new_reader_id_back(100);
+        assert_eq!(buffer.read(&mut none).len(), 0);
         assert_eq!(
-            vec![Test { id: 1 }, Test { id: 0 }],
-            buffer
-                .read(&mut reader_id)
-                .rev()
-                .cloned()
-                .collect::<Vec<_>>()
+            buffer.read(&mut two).cloned().collect::<Vec<_>>(),
+            vec![Test { id: 1 }, Test { id: 2 }]
         );
 
+        // `all` still holds on to all three events, so this must grow the buffer
+        buffer.drain_vec_write(&mut events(3));
+        let mut expected = events(3);
+        expected.extend(events(3));
+        assert_eq!(buffer.read(&mut all).cloned().collect::<Vec<_>>(), expected);
+
+        // Clamped to the initialized slots
+        let mut six = buffer.new_reader_id_back(buffer.capacity());
+        assert_eq!(buffer.read(&mut six).len(), 6);
+        assert_eq!(buffer.read(&mut two).len(), 3);
+
+        // A full lap
+        buffer.drain_vec_write(&mut events(2));
+        assert_eq!(buffer.data.num_initialized(), buffer.capacity());
+        let mut full = buffer.new_reader_id_back(buffer.capacity());
+        assert_eq!(buffer.read(&mut full).len(), buffer.capacity());
+
+        let mut lagging = buffer.new_reader_id_back(buffer.capacity());
+        buffer.single_write(Test { id: 42 });
+        assert_eq!(buffer.read(&mut lagging).len(), 9);
         assert_eq!(
-            Vec::<Test>::new(),
-            buffer
-                .read(&mut reader_id)
-                .rev()
-                .cloned()
-                .collect::<Vec<_>>()
+            buffer.read(&mut full).cloned().collect::<Vec<_>>(),
+            vec![Test { id: 42 }]
         );
     }
 
     #[test]
-    fn test_read_wrap_around_rev() {
+    fn test_iter_all_drain() {
         let mut buffer = RingBuffer::<Test>::new(4);
-        let mut reader_id = buffer.new_reader_id();
-        buffer.drain_vec_write(&mut events(2));
-        // buffer should now be [--> 0, 1, -, -]
+        assert_eq!(buffer.iter_all().len(), 0);
+
+        buffer.drain_vec_write(&mut events(3));
+        assert_eq!(buffer.iter_all().cloned().collect::<Vec<_>>(), events(3));
+
+        // Overwrites the oldest events
+        buffer.drain_vec_write(&mut events(3));
         assert_eq!(
-            vec![Test { id: 1 }, Test { id: 0 }],
-            buffer
-                .read(&mut reader_id)
-                .rev()
-                .cloned()
-                .collect::<Vec<_>>()
+            buffer.iter_all().cloned().collect::<Vec<_>>(),
+            vec![
+                Test { id: 2 },
+                Test { id: 0 },
+                Test { id: 1 },
+                Test { id: 2 }
+            ]
         );
-        buffer.drain_vec_write(&mut events(3));
-        // buffer should now be [2, 1, --> 0, 1]
+
+        let mut reader_id = buffer.new_reader_id_back(2);
         assert_eq!(
-            vec![Test { id: 2 }, Test { id: 1 }, Test { id: 0 }],
-            buffer
-                .read(&mut reader_id)
-                .rev()
-                .cloned()
-                .collect::<Vec<_>>()
+            buffer.drain(),
+            vec![
+                Test { id: 2 },
+                Test { id: 0 },
+                Test { id: 1 },
+                Test { id: 2 }
+            ]
         );
+        assert_eq!(buffer.data.num_initialized(), 0);
+        assert_eq!(buffer.iter_all().len(), 0);
+        assert_eq!(buffer.read(&mut reader_id).len(), 0);
 
+        buffer.drain_vec_write(&mut events(2));
+        assert_eq!(buffer.iter_all().cloned().collect::<Vec<_>>(), events(2));
         assert_eq!(
-            Vec::<Test>::new(),
-            buffer
-                .read(&mut reader_id)
-                .rev()
-                .cloned()
-                .collect::<Vec<_>>()
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>(),
+            events(2)
         );
     }
 
     #[test]
-    fn test_read_wrap_around_overflow_rev() {
-        let mut buffer = RingBuffer::<Test>::new(5);
+    fn test_len_partial_iteration() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(3));
+        buffer.read(&mut reader_id).for_each(drop);
+
+        // Spans the wrap: starts at index 3 and ends at index 2
+        buffer.drain_vec_write(&mut events(4));
+        let mut iter = buffer.read(&mut reader_id);
+        for remaining in (0..4).rev() {
+            assert!(iter.next().is_some());
+            assert_eq!(iter.len(), remaining);
+            assert_eq!(iter.size_hint(), (remaining, Some(remaining)));
+        }
+        assert!(iter.next().is_none());
+        assert_eq!(iter.len(), 0);
+
+        // Consuming from both ends moves `end` back across index 0
+        buffer.drain_vec_write(&mut events(4));
+        let mut iter = buffer.read(&mut reader_id);
+        assert_eq!(iter.next_back(), Some(&Test { id: 3 }));
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next_back(), Some(&Test { id: 2 }));
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.next_back(), Some(&Test { id: 1 }));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next(), Some(&Test { id: 0 }));
+        assert_eq!(iter.len(), 0);
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn test_scrub() {
+        let mut buffer = RingBuffer::<u32>::new(2);
+        buffer.set_scrub(true);
         let mut reader_id = buffer.new_reader_id();
+
+        // Overwrites the oldest slot
+        buffer.iter_write(1..3);
+        buffer.read(&mut reader_id).for_each(drop);
+        buffer.single_write(3);
+        buffer.read(&mut reader_id).for_each(drop);
+
+        // Grows, which moves the elements to a new allocation
+        buffer.iter_write(4..7);
+        assert!(buffer.capacity() > 2);
+        assert_eq!(
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>(),
+            vec![4, 5, 6]
+        );
+
+        assert!(buffer.drain().ends_with(&[4, 5, 6]));
+        let slots = unsafe {
+            std::slice::from_raw_parts(buffer.data.data.as_ptr(), buffer.data.data.len())
+        };
+        assert!(slots.iter().all(|&slot| slot == 0));
+    }
+
+    #[test]
+    fn test_reader_token() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut other = RingBuffer::<Test>::new(4);
+        let reader_id = buffer.new_reader_id();
+        let token = reader_id.token();
+        assert!(buffer.is_active(token));
+        assert!(!other.is_active(token));
+
+        drop(reader_id);
+        assert!(!buffer.is_active(token));
+
+        // Reuses the slot of the dropped reader
+        let reader_id = buffer.new_reader_id();
+        assert!(!buffer.is_active(token));
+        assert!(buffer.is_active(reader_id.token()));
+    }
+
+    #[test]
+    fn test_drain_matching() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut ahead = buffer.new_reader_id();
         buffer.drain_vec_write(&mut events(2));
+        let mut behind = buffer.new_reader_id_back(1);
+        buffer.read(&mut ahead).for_each(drop);
+        buffer.drain_vec_write(&mut events(4));
+
+        // `ahead` has 0..4 pending, `behind` additionally the second 1
+        let removed = buffer.drain_matching(|e| e.id % 2 == 1);
         assert_eq!(
-            vec![Test { id: 1 }, Test { id: 0 }],
-            buffer
-                .read(&mut reader_id)
-                .rev()
-                .cloned()
-                .collect::<Vec<_>>()
+            removed,
+            vec![Test { id: 1 }, Test { id: 1 }, Test { id: 3 }]
+        );
+        assert_eq!(
+            buffer.iter_all().cloned().collect::<Vec<_>>(),
+            vec![Test { id: 0 }, Test { id: 0 }, Test { id: 2 }]
         );
-        buffer.drain_vec_write(&mut events(6));
-        // buffer should now be [2, 1, --> 0, 1]
         assert_eq!(
-            events(6).into_iter().rev().collect::<Vec<_>>(),
             buffer
-                .read(&mut reader_id)
-                .rev()
+                .read_checked(&mut ahead)
+                .unwrap()
                 .cloned()
-                .collect::<Vec<_>>()
+                .collect::<Vec<_>>(),
+            vec![Test { id: 0 }, Test { id: 2 }]
         );
-
         assert_eq!(
-            Vec::<Test>::new(),
             buffer
-                .read(&mut reader_id)
-                .rev()
+                .read_checked(&mut behind)
+                .unwrap()
                 .cloned()
-                .collect::<Vec<_>>()
+                .collect::<Vec<_>>(),
+            vec![Test { id: 0 }, Test { id: 2 }]
         );
+
+        assert!(buffer.drain_matching(|_| false).is_empty());
+        buffer.drain_vec_write(&mut events(5));
+        assert_eq!(buffer.read(&mut ahead).len(), 5);
+        assert_eq!(buffer.read(&mut behind).len(), 5);
+
+        // Sequence numbers are reassigned, the newest element keeps its own
+        let written = buffer.total_written();
+        let ids = |iter: Option<StorageIterator<'_, Test>>| {
+            iter.unwrap().map(|t| t.id).collect::<Vec<_>>()
+        };
+        assert_eq!(ids(buffer.events_between(written - 3, written)), [2, 3, 4]);
+        buffer.drain_matching(|e| e.id == 3);
+        assert_eq!(ids(buffer.events_between(written - 3, written)), [1, 2, 4]);
+
+        // The element before the start of a reader doesn't count as read
+        let mut late = buffer.new_reader_id_back(2);
+        buffer.drain_matching(|e| e.id == 2);
+        let windows = buffer.read_windows(&mut late, true).count();
+        assert_eq!(windows, 0);
     }
 
     #[test]
-    fn test_write_overflow() {
-        let mut buffer = RingBuffer::<Test>::new(3);
-        let mut reader_id = buffer.new_reader_id();
+    fn test_drain_matching_position() {
+        let mut buffer = RingBuffer::<Test>::new(8);
+        buffer.set_retention(8);
+        let ids = |iter: StorageIterator<'_, Test>| iter.map(|t| t.id).collect::<Vec<_>>();
+        buffer.drain_vec_write(&mut events(2));
+        let pos = buffer.position();
         buffer.drain_vec_write(&mut events(4));
-        let data = buffer.read(&mut reader_id);
-        assert_eq!(
-            vec![
-                Test { id: 0 },
-                Test { id: 1 },
-                Test { id: 2 },
-                Test { id: 3 },
-            ],
-            data.cloned().collect::<Vec<_>>()
-        );
+
+        // Nothing removed, the position stays valid
+        assert!(buffer.drain_matching(|_| false).is_empty());
+        assert_eq!(ids(buffer.read_since(pos).0), [0, 1, 2, 3]);
+
+        // The elements before the removed one move up, which would make `pos`
+        // point at the second element of the first write. Instead it's stale.
+        buffer.drain_matching(|e| e.id == 3);
+        let (iter, new) = buffer.read_since(pos);
+        assert_eq!(ids(iter), [0, 1, 0, 1, 2]);
+        assert_eq!(pos.partial_cmp(&new), None);
+        assert!(pos != buffer.position());
+        assert_eq!(ids(buffer.read_since(new).0), Vec::<u32>::new());
     }
 
-    /// If you're getting a compilation error here this test has failed!
     #[test]
-    fn test_send_sync() {
-        trait SendSync: Send + Sync {
-            fn is_send_sync() -> bool;
-        }
+    fn test_nearest_cache() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut a = buffer.new_reader_id();
+        let mut b = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(2));
+        buffer.read(&mut a).for_each(drop);
 
-        impl<T> SendSync for T
-        where
-            T: Send + Sync,
-        {
-            fn is_send_sync() -> bool {
-                true
-            }
-        }
+        // Grows and caches `b` as the nearest reader
+        buffer.drain_vec_write(&mut events(3));
+        assert_eq!(buffer.capacity(), 8);
 
-        assert!(RingBuffer::<Test>::is_send_sync());
-        assert!(ReaderId::<Test>::is_send_sync());
-    }
+        // Now `a` is the nearest reader and must not be overwritten
+        assert_eq!(buffer.read(&mut b).len(), 5);
+        buffer.drain_vec_write(&mut events(6));
+        let mut expected = events(3);
+        expected.extend(events(6));
+        assert_eq!(buffer.read(&mut a).cloned().collect::<Vec<_>>(), expected);
 
-    #[test]
-    fn test_reader_reuse() {
-        let mut buffer = RingBuffer::<Test>::new(3);
-        {
-            let _reader_id = buffer.new_reader_id();
-        }
-        let _reader_id = buffer.new_reader_id();
-        assert_eq!(_reader_id.id, 0);
-        assert_eq!(buffer.meta.readers.len(), 1);
+        // Dropping the cached reader allows overwriting its elements
+        assert_eq!(buffer.max_lag(), 6);
+        drop(b);
+        let capacity = buffer.capacity();
+        buffer.drain_vec_write(&mut events(capacity as u32));
+        assert_eq!(buffer.capacity(), capacity);
+        assert_eq!(buffer.read(&mut a).len(), capacity);
     }
 
     #[test]
-    fn test_prevent_excess_growth() {
-        let mut buffer = RingBuffer::<Test>::new(3);
-        let mut reader_id = buffer.new_reader_id();
-        println!("Initial buffer state: {:#?}", buffer);
-        println!("--- first write ---");
-        buffer.drain_vec_write(&mut events(2));
-        println!("--- second write ---");
-        buffer.drain_vec_write(&mut events(2));
-        println!("--- writes complete ---");
-        // we wrote 0,1,0,1, if the buffer grew correctly we'll get all of these back.
+    fn test_events_between() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        assert_eq!(buffer.events_between(0, 0).map(|iter| iter.len()), Some(0));
+        assert!(buffer.events_between(0, 1).is_none());
+
+        // Sequence numbers 0..6, of which 2..6 are still stored
+        buffer.drain_vec_write(&mut events(6));
+        assert!(buffer.events_between(1, 3).is_none());
+        assert!(buffer.events_between(3, 7).is_none());
+        assert!(buffer.events_between(4, 3).is_none());
         assert_eq!(
-            vec![
-                Test { id: 0 },
-                Test { id: 1 },
-                Test { id: 0 },
-                Test { id: 1 },
-            ],
-            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+            buffer
+                .events_between(3, 6)
+                .unwrap()
+                .cloned()
+                .collect::<Vec<_>>(),
+            vec![Test { id: 3 }, Test { id: 4 }, Test { id: 5 }]
         );
-
-        buffer.drain_vec_write(&mut events(4));
-        // After writing 4 more events the buffer should have no reason to grow beyond 6
-        // (2 * 3).
-        assert_eq!(buffer.data.num_initialized(), 6);
         assert_eq!(
-            vec![
-                Test { id: 0 },
-                Test { id: 1 },
-                Test { id: 2 },
-                Test { id: 3 },
-            ],
-            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+            buffer
+                .events_between(2, 4)
+                .unwrap()
+                .cloned()
+                .collect::<Vec<_>>(),
+            vec![Test { id: 2 }, Test { id: 3 }]
         );
+        assert_eq!(buffer.events_between(5, 5).unwrap().len(), 0);
     }
 
     #[test]
-    fn test_write_slice() {
-        let mut buffer = RingBuffer::<Test>::new(10);
+    fn test_indexed() {
+        let mut buffer = RingBuffer::<Test>::new(4);
         let mut reader_id = buffer.new_reader_id();
-        buffer.iter_write(events(2));
-        let data = buffer.read(&mut reader_id);
+        buffer.drain_vec_write(&mut events(3));
+        buffer.read(&mut reader_id).for_each(drop);
+        buffer.drain_vec_write(&mut events(2));
+
+        let indexed = buffer.read(&mut reader_id).indexed();
+        assert_eq!(indexed.len(), 2);
         assert_eq!(
-            vec![Test { id: 0 }, Test { id: 1 }],
-            data.cloned().collect::<Vec<_>>()
+            indexed.collect::<Vec<_>>(),
+            vec![(3, &Test { id: 0 }), (0, &Test { id: 1 })]
         );
-    }
-
-    #[test]
-    fn iter_write_empty() {
-        let mut buffer = RingBuffer::<Test>::new(10);
-        let mut reader_id = buffer.new_reader_id();
-        buffer.iter_write(Vec::new());
-        let mut data = buffer.read(&mut reader_id);
-        assert_eq!(None, data.next());
+        assert_eq!(buffer.read(&mut reader_id).indexed().len(), 0);
     }
 
     fn events(n: u32) -> Vec<Test> {
@@ -0,0 +1,132 @@
+//! A reader bundled with the channel it reads from.
+
+use crate::{Event, EventChannel, EventIterator, ReaderId};
+
+/// A registered reader together with a shared reference to its channel,
+/// see `EventChannel::subscribe`.
+///
+/// Reading only needs the shared reference, so the channel doesn't have to
+/// be passed to every call. Dropping the `Subscription` drops its
+/// `ReaderId`, which unregisters the reader like usual.
+///
+/// The channel stays borrowed for as long as the `Subscription` exists, so
+/// nothing can be written in the meantime. For a reader which outlives
+/// several rounds of writes, keep the `ReaderId` with `into_reader_id` and
+/// bundle it again with `Subscription::new` whenever the channel is shared
+/// for reading.
+#[derive(Debug)]
+pub struct Subscription<'a, E: Event> {
+    channel: &'a EventChannel<E>,
+    reader_id: ReaderId<E>,
+}
+
+impl<'a, E> Subscription<'a, E>
+where
+    E: Event,
+{
+    /// Bundles `reader_id` with `channel`, which it has to belong to.
+    ///
+    /// This allows registering the readers while the channel is still
+    /// borrowed mutably, and subscribing once it is shared.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `reader_id` doesn't belong to `channel`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use shrev::{EventChannel, Subscription};
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let reader = channel.register_reader();
+    /// channel.iter_write(0..3);
+    ///
+    /// let mut subscription = Subscription::new(&channel, reader);
+    /// assert_eq!(subscription.unread_count(), 3);
+    /// assert_eq!(subscription.read().cloned().collect::<Vec<_>>(), vec![0, 1, 2]);
+    /// assert!(subscription.is_caught_up());
+    ///
+    /// // Keep the reader for the next round of writes
+    /// let reader = subscription.into_reader_id();
+    /// channel.single_write(3);
+    /// let mut subscription = Subscription::new(&channel, reader);
+    /// assert_eq!(subscription.read().cloned().collect::<Vec<_>>(), vec![3]);
+    ///
+    /// // Drop the reader again
+    /// drop(subscription);
+    /// assert_eq!(channel.reader_count(), 0);
+    /// ```
+    pub fn new(channel: &'a EventChannel<E>, reader_id: ReaderId<E>) -> Self {
+        // Fails early instead of on the first read
+        channel.unread_count(&reader_id);
+
+        Subscription { channel, reader_id }
+    }
+
+    /// Read the events written since the last read, see
+    /// `EventChannel::read`.
+    pub fn read(&mut self) -> EventIterator<'a, E> {
+        self.channel.read(&mut self.reader_id)
+    }
+
+    /// Returns the events the next `read` would return, without advancing
+    /// the reader.
+    pub fn peek(&self) -> EventIterator<'a, E> {
+        self.channel.peek(&self.reader_id)
+    }
+
+    /// Returns the number of events the next `read` would return.
+    pub fn unread_count(&self) -> usize {
+        self.channel.unread_count(&self.reader_id)
+    }
+
+    /// Returns `true` if every event written so far was read.
+    pub fn is_caught_up(&self) -> bool {
+        self.channel.is_caught_up(&self.reader_id)
+    }
+
+    /// Returns the channel this subscription reads from.
+    pub fn channel(&self) -> &'a EventChannel<E> {
+        self.channel
+    }
+
+    /// Returns the `ReaderId`, keeping the reader registered.
+    pub fn into_reader_id(self) -> ReaderId<E> {
+        self.reader_id
+    }
+}
+
+impl<E> EventChannel<E>
+where
+    E: Event,
+{
+    /// Register a new reader and return it bundled with the channel, as a
+    /// `Subscription`.
+    ///
+    /// The channel stays borrowed for as long as the `Subscription` exists,
+    /// see `Subscription` for reading across writes.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::<u32>::new();
+    /// let subscription = channel.subscribe();
+    /// assert!(subscription.is_caught_up());
+    ///
+    /// // Keep the reader to write more events
+    /// let mut reader = subscription.into_reader_id();
+    /// channel.iter_write(0..3);
+    /// assert_eq!(channel.read(&mut reader).len(), 3);
+    /// ```
+    pub fn subscribe(&mut self) -> Subscription<'_, E> {
+        let reader_id = self.register_reader();
+
+        Subscription {
+            channel: self,
+            reader_id,
+        }
+    }
+}
@@ -1,8 +1,111 @@
+#[cfg(feature = "allocator-api2")]
+use allocator_api2::alloc::{AllocError, Allocator, Layout};
+#[cfg(feature = "allocator-api2")]
+use std::ptr::NonNull;
+#[cfg(feature = "check_reentrancy")]
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::{
     fmt::{self, Debug, Formatter},
     sync::Arc,
 };
 
+#[cfg(feature = "loom")]
+pub use loom::cell::UnsafeCell;
+#[cfg(feature = "loom")]
+pub use loom::sync::mpsc;
+#[cfg(not(feature = "loom"))]
+pub use std::sync::mpsc;
+
+/// The vector type backing the ring buffer.
+#[cfg(not(feature = "allocator-api2"))]
+pub type Buf<T> = Vec<T>;
+
+/// The vector type backing the ring buffer.
+#[cfg(feature = "allocator-api2")]
+pub type Buf<T> = allocator_api2::vec::Vec<T, SharedAlloc>;
+
+/// Creates an empty buffer using the global allocator.
+pub fn new_buf<T>() -> Buf<T> {
+    #[cfg(not(feature = "allocator-api2"))]
+    return Vec::new();
+    #[cfg(feature = "allocator-api2")]
+    return Buf::new_in(SharedAlloc::new(allocator_api2::alloc::Global));
+}
+
+/// Creates an empty buffer with room for `capacity` elements, using the same
+/// allocator as `buf`.
+pub fn buf_like<T>(buf: &Buf<T>, capacity: usize) -> Buf<T> {
+    #[cfg(not(feature = "allocator-api2"))]
+    return {
+        let _ = buf;
+        Vec::with_capacity(capacity)
+    };
+    #[cfg(feature = "allocator-api2")]
+    return Buf::with_capacity_in(capacity, buf.allocator().clone());
+}
+
+/// A type-erased allocator, so the allocator doesn't have to be a type
+/// parameter of `EventChannel`.
+#[cfg(feature = "allocator-api2")]
+#[derive(Clone)]
+pub struct SharedAlloc(Arc<dyn Allocator + Send + Sync>);
+
+#[cfg(feature = "allocator-api2")]
+impl SharedAlloc {
+    pub fn new<A>(alloc: A) -> Self
+    where
+        A: Allocator + Send + Sync + 'static,
+    {
+        SharedAlloc(Arc::new(alloc))
+    }
+}
+
+#[cfg(feature = "allocator-api2")]
+unsafe impl Allocator for SharedAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.0.deallocate(ptr, layout)
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.grow(ptr, old_layout, new_layout)
+    }
+}
+
+/// A thin wrapper around `std::cell::UnsafeCell` exposing the same closure
+/// based API as `loom::cell::UnsafeCell`.
+///
+/// All accesses to shared reader state go through closures, so
+/// enabling the `loom` feature lets `loom` track every one of them.
+#[cfg(not(feature = "loom"))]
+#[derive(Debug)]
+pub struct UnsafeCell<T>(std::cell::UnsafeCell<T>);
+
+#[cfg(not(feature = "loom"))]
+impl<T> UnsafeCell<T> {
+    pub fn new(t: T) -> Self {
+        UnsafeCell(std::cell::UnsafeCell::new(t))
+    }
+
+    #[inline]
+    pub fn with<R>(&self, f: impl FnOnce(*const T) -> R) -> R {
+        f(self.0.get())
+    }
+
+    #[inline]
+    pub fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R {
+        f(self.0.get())
+    }
+}
+
 /// A unique ID that can be used to assert two objects refer to another common
 /// object.
 ///
@@ -117,3 +220,51 @@ where
 }
 
 unsafe impl<T> Sync for NoSharedAccess<T> where for<'a> &'a mut NoSharedAccess<T>: Send {}
+
+/// Runs the closure when dropped, including while unwinding.
+pub struct OnDrop<F: FnMut()>(pub F);
+
+impl<F: FnMut()> Drop for OnDrop<F> {
+    fn drop(&mut self) {
+        (self.0)();
+    }
+}
+
+/// Tracks whether a write is in progress, see the `check_reentrancy` feature.
+#[cfg(feature = "check_reentrancy")]
+#[derive(Debug, Default)]
+pub struct WriteFlag(AtomicBool);
+
+#[cfg(feature = "check_reentrancy")]
+impl WriteFlag {
+    /// Marks a write as in progress until `exit` is called.
+    ///
+    /// Panics if another write is in progress.
+    pub fn enter(&self) {
+        assert!(
+            !self.0.swap(true, Ordering::Acquire),
+            "reentrant or concurrent write to `EventChannel`"
+        );
+    }
+
+    /// Ends the write started by `enter`.
+    pub fn exit(&self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(all(test, feature = "check_reentrancy"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_flag() {
+        let flag = WriteFlag::default();
+        flag.enter();
+        flag.exit();
+        flag.enter();
+
+        let result = std::panic::catch_unwind(|| flag.enter());
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,82 @@
+//! Drops `ReaderId`s on other threads while the writer frees their slots.
+//!
+//! The `loom` tests need to be run with
+//! `cargo test --release --features loom --test concurrent_drop`.
+
+#[cfg(not(feature = "loom"))]
+#[test]
+fn drop_readers_on_threads() {
+    use shrev::EventChannel;
+
+    let mut channel = EventChannel::with_capacity(4);
+    let mut kept = channel.register_reader();
+
+    for round in 0..100 {
+        let dropped = (0..8)
+            .map(|_| channel.register_reader())
+            .collect::<Vec<_>>();
+        let tokens = dropped.iter().map(|r| r.token()).collect::<Vec<_>>();
+
+        std::thread::scope(|s| {
+            for reader in dropped {
+                s.spawn(move || drop(reader));
+            }
+
+            channel.iter_write(round * 4..round * 4 + 4);
+        });
+
+        assert_eq!(channel.reader_count(), 1);
+        assert!(tokens.into_iter().all(|t| !channel.is_active(t)));
+        let events = channel.read(&mut kept).cloned().collect::<Vec<_>>();
+        assert_eq!(events, (round * 4..round * 4 + 4).collect::<Vec<_>>());
+    }
+}
+
+#[cfg(feature = "loom")]
+#[test]
+fn drop_readers_on_threads() {
+    use loom::thread;
+    use shrev::EventChannel;
+
+    loom::model(|| {
+        let mut channel = EventChannel::with_capacity(2);
+        let mut kept = channel.register_reader();
+        let first = channel.register_reader();
+        let second = channel.register_reader();
+        let tokens = [first.token(), second.token()];
+
+        let handles = vec![
+            thread::spawn(move || drop(first)),
+            thread::spawn(move || drop(second)),
+        ];
+        // Frees whatever has been dropped so far, concurrently with the drops.
+        channel.iter_write(0..3);
+        assert!(channel.is_active(kept.token()));
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(channel.reader_count(), 1);
+        assert!(tokens.iter().all(|&t| !channel.is_active(t)));
+
+        // A freed slot is handed out once, so the new readers do not alias.
+        let mut new = channel.register_reader();
+        let mut other = channel.register_reader();
+        assert_ne!(new.token(), other.token());
+        assert_eq!(channel.reader_count(), 3);
+
+        channel.iter_write(3..5);
+        assert_eq!(
+            channel.read(&mut kept).cloned().collect::<Vec<i32>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+        assert_eq!(
+            channel.read(&mut new).cloned().collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+        assert_eq!(
+            channel.read(&mut other).cloned().collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+    });
+}
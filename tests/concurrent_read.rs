@@ -0,0 +1,62 @@
+//! Reads with distinct `ReaderId`s from several threads at once.
+//!
+//! The `loom` tests need to be run with
+//! `cargo test --release --features loom --test concurrent_read`.
+
+#[cfg(not(feature = "loom"))]
+#[test]
+fn distinct_readers_on_threads() {
+    use shrev::EventChannel;
+
+    let mut channel = EventChannel::with_capacity(4);
+    let mut readers = (0..8)
+        .map(|_| channel.register_reader())
+        .collect::<Vec<_>>();
+
+    for round in 0..100 {
+        channel.iter_write(round * 10..round * 10 + 10);
+
+        let channel = &channel;
+        std::thread::scope(|s| {
+            for reader in &mut readers {
+                s.spawn(move || {
+                    let events = channel.read(reader).cloned().collect::<Vec<_>>();
+                    assert_eq!(events, (round * 10..round * 10 + 10).collect::<Vec<_>>());
+                });
+            }
+        });
+    }
+}
+
+#[cfg(feature = "loom")]
+#[test]
+fn distinct_readers_on_threads() {
+    use loom::{sync::Arc, thread};
+    use shrev::EventChannel;
+
+    loom::model(|| {
+        let mut channel = EventChannel::with_capacity(2);
+        let mut first = channel.register_reader();
+        let mut second = channel.register_reader();
+        channel.iter_write(0..3);
+
+        // The readers are dropped before the channel, as loom counts ids
+        // sent to a dropped channel as leaked
+        let channel = Arc::new(channel);
+        let handle = {
+            let channel = channel.clone();
+
+            thread::spawn(move || {
+                let events = channel.read(&mut first).cloned().collect::<Vec<i32>>();
+                drop(first);
+
+                events
+            })
+        };
+        let events = channel.read(&mut second).cloned().collect::<Vec<_>>();
+        drop(second);
+
+        assert_eq!(events, vec![0, 1, 2]);
+        assert_eq!(handle.join().unwrap(), vec![0, 1, 2]);
+    });
+}
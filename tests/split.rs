@@ -0,0 +1,57 @@
+use std::thread;
+
+use shrev::EventChannel;
+
+#[test]
+fn producer_consumers() {
+    let (mut producer, consumer) = EventChannel::with_capacity(4).split();
+    let mut first = consumer();
+
+    producer.iter_write(0..3);
+
+    let mut second = consumer();
+    producer.iter_write(3..6);
+
+    assert_eq!(
+        first.read(|events| events.cloned().collect::<Vec<_>>()),
+        vec![0, 1, 2, 3, 4, 5]
+    );
+    assert_eq!(
+        second.read(|events| events.cloned().collect::<Vec<_>>()),
+        vec![3, 4, 5]
+    );
+    assert_eq!(first.read(|events| events.len()), 0);
+}
+
+#[test]
+fn consumers_on_threads() {
+    let (mut producer, consumer) = EventChannel::new().split();
+    let consumers = (0..4).map(|_| consumer()).collect::<Vec<_>>();
+
+    producer.iter_write(0..100u32);
+    assert!(producer.would_write());
+
+    let handles = consumers
+        .into_iter()
+        .map(|mut consumer| thread::spawn(move || consumer.read(|events| events.sum::<u32>())))
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 4950);
+    }
+    assert!(!producer.would_write());
+}
+
+#[test]
+fn readers_from_before_the_split() {
+    let mut channel = EventChannel::with_capacity(4);
+    let reader = channel.register_reader();
+    let (mut producer, _consumer) = channel.split();
+
+    // The old reader is still registered
+    assert!(producer.would_write());
+    producer.iter_write(0..8);
+
+    drop(reader);
+    assert!(!producer.would_write());
+}